@@ -0,0 +1,137 @@
+//! Receiving webhooks in an [axum](https://docs.rs/axum) app. Needs the `axum` feature.
+
+use std::sync::Arc;
+use ::axum::async_trait;
+use ::axum::body::HttpBody;
+use ::axum::extract::{FromRef, FromRequest, State};
+use ::axum::http::{header, Request, StatusCode};
+use ::axum::routing::post;
+use ::axum::{BoxError, Router};
+use bytes::Buf;
+
+use crate::webhook::events::{Broadcast, Events};
+use crate::webhook::{self, is_authorized, Event, Overflow, DEFAULT_QUEUE_CAPACITY};
+
+
+
+/// The largest body read, the same as the warp server's default and much bigger than any webhook
+/// top.gg sends.
+const BODY_LIMIT: u64 = 16 * 1024;
+
+
+/// Returns a router which receives webhooks on `/` and the stream of events it receives. Nest it
+/// wherever top.gg should send webhooks to.
+/// ## Examples
+/// ```
+/// let (webhook, events) = topgg::axum::webhook(auth);
+/// let app = axum::Router::new()
+///     .route("/health", axum::routing::get(|| async { "ok" }))
+///     .nest("/topgg/webhook", webhook);
+/// ```
+pub fn webhook(auth: String) -> (Router, Events<Event>) {
+    let broadcast = Arc::new(Broadcast::new(Some(DEFAULT_QUEUE_CAPACITY), Overflow::DropOldest));
+    let events = broadcast.subscribe();
+    let router = Router::new()
+        .route("/", post(receive))
+        .with_state(WebhookState {
+            auth: WebhookAuth(auth),
+            broadcast,
+        });
+    (router, events)
+}
+
+
+async fn receive(State(state): State<WebhookState>, TopggVote(event): TopggVote) -> StatusCode {
+    match state.broadcast.send(event) {
+        Ok(()) => StatusCode::OK,
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+
+#[derive(Clone)]
+struct WebhookState {
+    auth: WebhookAuth,
+    broadcast: Arc<Broadcast<Event>>,
+}
+impl FromRef<WebhookState> for WebhookAuth {
+    fn from_ref(state: &WebhookState) -> WebhookAuth {
+        state.auth.clone()
+    }
+}
+
+
+/// The authorization string set on your top.gg webhook page. The `TopggVote` extractor gets this
+/// from your router's state, so it needs to be in it (or `FromRef` implemented for your state).
+#[derive(Clone, Debug)]
+pub struct WebhookAuth(pub String);
+
+
+/// Extracts a webhook from the request after checking its authorization header, rejecting the
+/// request with a 401 or 400 if it isn't a valid webhook from top.gg.
+/// ## Examples
+/// ```
+/// use topgg::axum::{TopggVote, WebhookAuth};
+///
+/// async fn vote(TopggVote(event): TopggVote) {
+///     println!("{:?}", event);
+/// }
+///
+/// let app = axum::Router::new()
+///     .route("/vote", axum::routing::post(vote))
+///     .with_state(WebhookAuth(auth));
+/// ```
+#[derive(Debug)]
+pub struct TopggVote(pub Event);
+
+#[async_trait]
+impl<S, B> FromRequest<S, B> for TopggVote
+where
+    WebhookAuth: FromRef<S>,
+    S: Send + Sync,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<TopggVote, StatusCode> {
+        let auth = WebhookAuth::from_ref(state);
+        let authorization = req.headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+        // Checked before reading the body, so unauthorized clients can't make the server read anything.
+        if !is_authorized(&[&auth.0], authorization) {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        let body = read_limited(req.into_body()).await?;
+
+        webhook::parse_body(&body)
+            .map(TopggVote)
+            .map_err(|e| StatusCode::from_u16(e.status()).unwrap_or(StatusCode::BAD_REQUEST))
+    }
+}
+
+/// Reads the body, failing with a 413 once it's over `BODY_LIMIT` rather than reading the rest.
+async fn read_limited<B: HttpBody>(body: B) -> Result<Vec<u8>, StatusCode> {
+    let hint = body.size_hint();
+    if hint.lower() > BODY_LIMIT || matches!(hint.upper(), Some(upper) if upper > BODY_LIMIT) {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    futures::pin_mut!(body);
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let mut chunk = chunk.map_err(|_| StatusCode::BAD_REQUEST)?;
+        if (bytes.len() + chunk.remaining()) as u64 > BODY_LIMIT {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        while chunk.has_remaining() {
+            let part = chunk.chunk();
+            let len = part.len();
+            bytes.extend_from_slice(part);
+            chunk.advance(len);
+        }
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,129 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use moka::future::Cache;
+
+use crate::{Bot, Snowflake, User};
+
+
+
+/// Caches the bots and users fetched by the client, see `Topgg::cache`.
+pub(crate) struct ResponseCache {
+    bots: Cache<Snowflake, Bot>,
+    users: Cache<Snowflake, User>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+impl ResponseCache {
+    pub(crate) fn new(max_bytes: u64, time_to_live: Duration, time_to_idle: Duration) -> ResponseCache {
+        // The bots and users share the space between them.
+        ResponseCache {
+            bots: Cache::builder()
+                .max_capacity(max_bytes / 2)
+                .weigher(|_, bot: &Bot| bot_size(bot))
+                .time_to_live(time_to_live)
+                .time_to_idle(time_to_idle)
+                .build(),
+            users: Cache::builder()
+                .max_capacity(max_bytes / 2)
+                .weigher(|_, user: &User| user_size(user))
+                .time_to_live(time_to_live)
+                .time_to_idle(time_to_idle)
+                .build(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+
+    pub(crate) async fn bot(&self, bot_id: Snowflake) -> Option<Bot> {
+        let bot = self.bots.get(&bot_id).await;
+        self.count(bot.is_some());
+        bot
+    }
+
+
+    pub(crate) async fn insert_bot(&self, bot: Bot) {
+        self.bots.insert(bot.id, bot).await;
+    }
+
+
+    pub(crate) async fn user(&self, user_id: Snowflake) -> Option<User> {
+        let user = self.users.get(&user_id).await;
+        self.count(user.is_some());
+        user
+    }
+
+
+    pub(crate) async fn insert_user(&self, user: User) {
+        self.users.insert(user.id, user).await;
+    }
+
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.bots.entry_count() + self.users.entry_count(),
+            bytes: self.bots.weighted_size() + self.users.weighted_size(),
+        }
+    }
+
+
+    fn count(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+
+/// How well the client's cache is doing, from `Topgg::cache_stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups answered from the cache.
+    pub hits: u64,
+    /// Lookups which had to be requested from top.gg.
+    pub misses: u64,
+    /// Bots and users in the cache. This is updated lazily, so may be a little behind.
+    pub entries: u64,
+    /// Roughly how much memory the entries use.
+    pub bytes: u64,
+}
+
+
+fn bot_size(bot: &Bot) -> u32 {
+    let strings = [
+        bot.username.len(),
+        bot.discriminator.len(),
+        bot.def_avatar.len(),
+        bot.lib.len(),
+        bot.prefix.len(),
+        bot.short_desc.len(),
+        #[cfg(not(feature = "chrono"))]
+        bot.date.len(),
+        bot.avatar.as_ref().map_or(0, String::len),
+        bot.long_desc.as_ref().map_or(0, String::len),
+        bot.website.as_ref().map_or(0, String::len),
+        bot.support.as_ref().map_or(0, String::len),
+        bot.github.as_ref().map_or(0, String::len),
+        bot.invite.as_ref().map_or(0, String::len),
+        bot.vanity.as_ref().map_or(0, String::len),
+    ];
+    let tags: usize = bot.tags.iter().map(String::len).sum();
+    let ids = (bot.owners.len() + bot.guilds.len()) * 8;
+    (std::mem::size_of::<Bot>() + strings.iter().sum::<usize>() + tags + ids) as u32
+}
+
+
+fn user_size(user: &User) -> u32 {
+    let strings = [
+        user.username.len(),
+        user.discriminator.len(),
+        user.def_avatar.len(),
+        user.avatar.as_ref().map_or(0, String::len),
+        user.bio.as_ref().map_or(0, String::len),
+        user.banner.as_ref().map_or(0, String::len),
+        user.color.as_ref().map_or(0, String::len),
+    ];
+    let social: usize = user.social.iter().map(|(network, account)| network.len() + account.len()).sum();
+    (std::mem::size_of::<User>() + strings.iter().sum::<usize>() + social) as u32
+}
@@ -0,0 +1,40 @@
+//! Building Discord CDN URLs for the images of bots and users.
+
+use crate::Snowflake;
+
+
+
+const CDN_URL: &str = "https://cdn.discordapp.com";
+
+
+/// The URL of an avatar, or of the default avatar Discord shows when there isn't one.
+pub(crate) fn avatar_url(id: Snowflake, discriminator: &str, avatar: Option<&str>, size: u16) -> String {
+    match avatar {
+        Some(hash) => image_url("avatars", id, hash, size),
+        None => default_avatar_url(id, discriminator),
+    }
+}
+
+
+/// The URL of the default avatar, picked from the discriminator, or from the ID for users on the new
+/// username system whose discriminator is `0`.
+pub(crate) fn default_avatar_url(id: Snowflake, discriminator: &str) -> String {
+    let index = match discriminator.parse::<u64>() {
+        Ok(discriminator) if discriminator != 0 => discriminator % 5,
+        _ => (id.get() >> 22) % 6,
+    };
+    format!("{}/embed/avatars/{}.png", CDN_URL, index)
+}
+
+
+/// The URL of an image uploaded to Discord, as a GIF if it's animated.
+pub(crate) fn image_url(kind: &str, id: Snowflake, hash: &str, size: u16) -> String {
+    let extension = if hash.starts_with("a_") { "gif" } else { "png" };
+    format!("{}/{}/{}/{}.{}?size={}", CDN_URL, kind, id, hash, extension, image_size(size))
+}
+
+
+/// Discord only serves sizes which are powers of two from 16 to 4096, so others are rounded up to one.
+fn image_size(size: u16) -> u16 {
+    size.clamp(16, 4096).next_power_of_two()
+}
@@ -0,0 +1,1656 @@
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use base64::Engine;
+use futures::channel::oneshot;
+use futures_util::future;
+
+use crate::{Bot, BotStats, Server, Snowflake, Social, User};
+use crate::webhook::Event;
+use breaker::CircuitBreaker;
+use ratelimit::RateLimit;
+pub use ratelimit::RateLimitStats;
+#[cfg(feature = "redis-ratelimit")]
+pub use ratelimit::RedisRateLimit;
+#[cfg(feature = "moka")]
+use crate::cache::{CacheStats, ResponseCache};
+
+#[cfg(feature = "metrics")]
+mod telemetry;
+#[cfg(feature = "otel")]
+mod otel;
+#[cfg(feature = "vcr")]
+mod vcr;
+#[cfg(feature = "vcr")]
+pub use vcr::{Cassette, VcrMode};
+mod builder;
+pub use builder::TopggBuilder;
+mod middleware;
+pub use middleware::{Middleware, RequestSummary};
+mod breaker;
+mod ratelimit;
+mod pool;
+pub use pool::TopggPool;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod voters;
+pub use voters::VoterDiff;
+#[cfg(feature = "discordbotlist")]
+mod discordbotlist;
+#[cfg(feature = "discordbotlist")]
+pub use discordbotlist::DiscordBotList;
+#[cfg(feature = "discords")]
+mod discords;
+#[cfg(feature = "discords")]
+pub use discords::Discords;
+
+
+
+const BASE_URL: &str = "https://top.gg/api";
+
+
+/// Identifies the library to top.gg, as it asks API clients to.
+pub const USER_AGENT: &str = concat!("topgg-rs/", env!("CARGO_PKG_VERSION"), " (https://github.com/14ROVI/topgg-rs)");
+
+
+/// The token, which with the `zeroize` or `secrecy` feature is wiped from memory when the client is dropped.
+#[cfg(feature = "secrecy")]
+type Token = secrecy::SecretString;
+#[cfg(all(feature = "zeroize", not(feature = "secrecy")))]
+type Token = zeroize::Zeroizing<String>;
+#[cfg(not(any(feature = "zeroize", feature = "secrecy")))]
+type Token = String;
+
+
+/// Wraps the token in whatever the enabled features keep it in.
+fn secure_token(token: String) -> Token {
+    #[cfg(feature = "secrecy")]
+    {
+        secrecy::SecretString::new(token)
+    }
+    #[cfg(all(feature = "zeroize", not(feature = "secrecy")))]
+    {
+        zeroize::Zeroizing::new(token)
+    }
+    #[cfg(not(any(feature = "zeroize", feature = "secrecy")))]
+    {
+        token
+    }
+}
+
+
+/// The token as a string, to send in the `Authorization` header.
+fn expose_token(token: &Token) -> &str {
+    #[cfg(feature = "secrecy")]
+    {
+        secrecy::ExposeSecret::expose_secret(token).as_str()
+    }
+    #[cfg(not(feature = "secrecy"))]
+    {
+        token.as_str()
+    }
+}
+
+
+/// An HTTP client with the library's User-Agent.
+pub(crate) fn default_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .expect("the HTTP client couldn't be set up")
+}
+
+
+/// This is the top.gg API client. It houses the functions needed to interact with their API.
+pub struct Topgg {
+    bot_id: Snowflake,
+    token: Token,
+    base_url: String,
+    client: reqwest::Client,
+    rate_limit: Arc<RateLimit>,
+    in_flight: InFlight,
+    voted_cache: Option<VotedCache>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    breaker: Option<CircuitBreaker>,
+    #[cfg(feature = "moka")]
+    cache: Option<ResponseCache>,
+    #[cfg(feature = "vcr")]
+    cassette: Option<Cassette>,
+}
+impl Topgg {
+    /// Returns a new client.
+    /// 
+    /// ## Arguments
+    /// * `bot_id` - The ID of your bot
+    /// * `token` - The top.gg token for that (or another valid) bot
+    /// 
+    /// ## Examples
+    /// ```
+    /// let client = topgg::Topgg::new(bot_id, token);
+    /// // Do stuff with the client
+    /// let votes = client.votes().await.unwrap();
+    /// ```
+    /// 
+    pub fn new(bot_id: impl Into<Snowflake>, token: String) -> Topgg {
+        Topgg::with_token(bot_id.into(), secure_token(token))
+    }
+
+
+    /// Returns a builder for a client whose HTTP client is configured, such as to go through a proxy.
+    /// ## Examples
+    /// ```
+    /// let client = topgg::Topgg::builder(bot_id, token)
+    ///     .proxy("socks5://127.0.0.1:1080")
+    ///     .build()?;
+    /// ```
+    pub fn builder(bot_id: impl Into<Snowflake>, token: String) -> TopggBuilder {
+        TopggBuilder::new(bot_id.into(), token)
+    }
+
+
+    /// Returns a new client using a token kept in a `SecretString`, so it's never held as a plain
+    /// `String`. Needs the `secrecy` feature.
+    /// ## Examples
+    /// ```
+    /// use secrecy::SecretString;
+    ///
+    /// let token = SecretString::new(std::env::var("TOPGG_TOKEN")?);
+    /// let client = topgg::Topgg::with_secret(bot_id, token);
+    /// ```
+    #[cfg(feature = "secrecy")]
+    pub fn with_secret(bot_id: impl Into<Snowflake>, token: secrecy::SecretString) -> Topgg {
+        Topgg::with_token(bot_id.into(), token)
+    }
+
+
+    /// Returns a new client using the token in the `TOPGG_TOKEN` environment variable, for the bot
+    /// whose ID is in `TOPGG_BOT_ID`. If `TOPGG_BOT_ID` isn't set the ID is read from the token, like
+    /// `from_token` does.
+    /// ## Examples
+    /// ```
+    /// let client = topgg::Topgg::from_env()?;
+    /// ```
+    pub fn from_env() -> Result<Topgg, ConfigError> {
+        let (bot_id, token) = env_config()?;
+        Ok(Topgg::new(bot_id, token))
+    }
+
+
+    /// Returns a new client for the bot the token belongs to. top.gg tokens are JWTs whose payload
+    /// holds the bot's ID, which is read without checking the signature since top.gg does that.
+    /// ## Examples
+    /// ```
+    /// let client = topgg::Topgg::from_token(token)?;
+    /// ```
+    pub fn from_token(token: String) -> Result<Topgg, ConfigError> {
+        let bot_id = token_bot_id(&token)?;
+        Ok(Topgg::new(bot_id, token))
+    }
+
+
+    fn with_token(bot_id: Snowflake, token: Token) -> Topgg {
+        Topgg::with_shared(bot_id, token, default_client(), Arc::new(RateLimit::new()))
+    }
+
+
+    /// Makes a client which sends its requests through `client` and shares `rate_limit` with the other
+    /// clients given it, as the ones in a `TopggPool` do.
+    fn with_shared(bot_id: Snowflake, token: Token, client: reqwest::Client, rate_limit: Arc<RateLimit>) -> Topgg {
+        Topgg {
+            bot_id: bot_id,
+            token: token,
+            base_url: BASE_URL.to_string(),
+            client: client,
+            rate_limit: rate_limit,
+            in_flight: InFlight::default(),
+            voted_cache: None,
+            middleware: Vec::new(),
+            breaker: None,
+            #[cfg(feature = "moka")]
+            cache: None,
+            #[cfg(feature = "vcr")]
+            cassette: None,
+        }
+    }
+
+
+    /// Sends requests to `url` instead of `https://top.gg/api`, such as a proxy in front of top.gg or
+    /// the fake server from `testing::FakeTopgg`.
+    /// ## Examples
+    /// ```
+    /// let client = topgg::Topgg::new(bot_id, token)
+    ///     .base_url("http://127.0.0.1:8080/api");
+    /// ```
+    pub fn base_url(mut self, url: impl Into<String>) -> Topgg {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+
+    /// Records the responses from top.gg to the cassette, or answers from the ones recorded there,
+    /// depending on its mode. Needs the `vcr` feature.
+    /// ## Examples
+    /// ```
+    /// use topgg::{Cassette, VcrMode};
+    ///
+    /// let mode = if std::env::var("TOPGG_TOKEN").is_ok() { VcrMode::Record } else { VcrMode::Replay };
+    /// let client = topgg::Topgg::new(bot_id, token)
+    ///     .vcr(Cassette::new("tests/fixtures/topgg", mode));
+    /// ```
+    #[cfg(feature = "vcr")]
+    pub fn vcr(mut self, cassette: Cassette) -> Topgg {
+        self.cassette = Some(cassette);
+        self
+    }
+
+
+    /// Stops sending requests for `cooldown` after `failures` in a row fail, so commands don't keep
+    /// waiting on top.gg while it's down. Requests made in the meantime fail straight away with a
+    /// `TopggError::Api` with status 503. After the cooldown one request is sent to check whether
+    /// top.gg has recovered, and requests are sent as normal again once one succeeds. Server errors
+    /// and requests that couldn't be sent count as failures.
+    /// ## Examples
+    /// ```
+    /// let client = topgg::Topgg::new(bot_id, token)
+    ///     .circuit_breaker(5, Duration::from_secs(30));
+    /// ```
+    pub fn circuit_breaker(mut self, failures: u32, cooldown: Duration) -> Topgg {
+        self.breaker = Some(CircuitBreaker::new(failures, cooldown));
+        self
+    }
+
+
+    /// Adds hooks run around every request, after any added before. See `Middleware`.
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Topgg {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+
+    /// Remembers the results of `voted` for `ttl`, so checking for a vote on every command doesn't
+    /// use up the rate limit. Votes received by the webhook can be passed to `ingest_vote` so they're
+    /// seen straight away instead of after the remembered result runs out.
+    /// ## Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = topgg::Topgg::new(bot_id, token)
+    ///     .cache_voted(Duration::from_secs(60));
+    /// ```
+    pub fn cache_voted(mut self, ttl: Duration) -> Topgg {
+        self.voted_cache = Some(VotedCache::new(ttl));
+        self
+    }
+
+
+    /// Records a vote received by the webhook in the cache set up with `cache_voted`, so `voted`
+    /// returns true for it without asking top.gg. Does nothing without that cache.
+    /// ## Examples
+    /// ```
+    /// while let Some(event) = events.next().await {
+    ///     client.ingest_vote(&event);
+    /// }
+    /// ```
+    pub fn ingest_vote(&self, event: &Event) {
+        if let (Some(cache), Event::BotVote(vote)) = (&self.voted_cache, event) {
+            cache.insert(vote.bot, vote.user, true);
+        }
+    }
+
+
+    /// Caches the bots and users fetched with `bot` and `user`, so looking the same ones up again
+    /// doesn't use up the rate limit. Needs the `moka` feature.
+    ///
+    /// ## Arguments
+    /// * `max_bytes` - Roughly how much memory the cache can use before the least used entries are evicted
+    /// * `time_to_live` - How long an entry is kept after it was fetched
+    /// * `time_to_idle` - How long an entry is kept after it was last looked up
+    ///
+    /// ## Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let client = topgg::Topgg::new(bot_id, token)
+    ///     .cache(10 * 1024 * 1024, Duration::from_secs(60 * 60), Duration::from_secs(10 * 60));
+    /// ```
+    #[cfg(feature = "moka")]
+    pub fn cache(mut self, max_bytes: u64, time_to_live: Duration, time_to_idle: Duration) -> Topgg {
+        self.cache = Some(ResponseCache::new(max_bytes, time_to_live, time_to_idle));
+        self
+    }
+
+
+    /// The hits and misses of the cache set up with `cache`, or `None` if there isn't one.
+    #[cfg(feature = "moka")]
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(ResponseCache::stats)
+    }
+
+
+    /// How many more requests can be sent to any route right now without waiting for the rate limit,
+    /// going by the route that has been used the most. Clients in a `TopggPool` share one.
+    pub fn remaining_quota(&self) -> u32 {
+        self.rate_limit.remaining()
+    }
+
+
+    /// How long until a request to any route can be sent without waiting, which is zero if one can be
+    /// sent now. This includes the pause after top.gg responds with a 429.
+    pub fn time_until_ready(&self) -> Duration {
+        self.rate_limit.time_until_ready()
+    }
+
+
+    /// How often and for how long requests have waited for the rate limit, for noticing when the bot
+    /// is using up its top.gg budget.
+    /// ## Examples
+    /// ```
+    /// let stats = client.rate_limit_stats();
+    /// println!("{} requests waited {:?} in total", stats.waits, stats.waited);
+    /// ```
+    pub fn rate_limit_stats(&self) -> RateLimitStats {
+        self.rate_limit.stats()
+    }
+
+
+    /// A shortcut for getting the botinfo for your own bot.
+    /// ## Examples
+    /// ```
+    /// let bot_info = client.my_bot().await.unwrap();
+    /// ```
+    pub async fn my_bot(&self) -> Option<Bot> {
+        self.bot(self.bot_id).await
+    }
+
+
+    /// Gets the info for a bot given an ID. To get the info for your own bot `client.my_bot()` can be used as a shortcut.
+    /// ## Examples
+    /// ```
+    /// let bot_info = client.bot(668701133069352961).await.unwrap();
+    /// ```
+    pub async fn bot(&self, bot_id: impl Into<Snowflake>) -> Option<Bot> {
+        let bot_id = bot_id.into();
+        #[cfg(feature = "moka")]
+        if let Some(cache) = &self.cache {
+            if let Some(bot) = cache.bot(bot_id).await {
+                return Some(bot);
+            }
+            let bot = self.fetch_bot(bot_id).await?;
+            cache.insert_bot(bot.clone()).await;
+            return Some(bot);
+        }
+        self.fetch_bot(bot_id).await
+    }
+
+
+    async fn fetch_bot(&self, bot_id: Snowflake) -> Option<Bot> {
+        let url = format!("{}/bots/{}", self.base_url, bot_id);
+        let res = self.get_json::<JsonBot>(&url).await?;
+
+        Some(Bot::from(res))
+    }
+
+
+    /// Gets the info for a user.
+    /// ## Examples
+    /// ```
+    /// client.user(195512978634833920).await.unwrap();
+    /// ```
+    pub async fn user(&self, user_id: impl Into<Snowflake>) -> Option<User> {
+        let user_id = user_id.into();
+        #[cfg(feature = "moka")]
+        if let Some(cache) = &self.cache {
+            if let Some(user) = cache.user(user_id).await {
+                return Some(user);
+            }
+            let user = self.fetch_user(user_id).await?;
+            cache.insert_user(user.clone()).await;
+            return Some(user);
+        }
+        self.fetch_user(user_id).await
+    }
+
+
+    async fn fetch_user(&self, user_id: Snowflake) -> Option<User> {
+        let url = format!("{}/users/{}", self.base_url, user_id);
+        let res = self.get_json::<JsonUser>(&url).await?;
+
+        Some(User::from(res))
+    }
+
+
+    /// A shortcut for getting the votes for the bot that created the client.
+    /// ## Examples
+    /// ```
+    /// let votes = client.my_votes().await.unwrap();
+    /// ```
+    pub async fn my_votes(&self) -> Option<Vec<Snowflake>> {
+        self.votes(self.bot_id).await
+    }
+
+
+    /// Gets the user IDs of all the users that have voted on the bot_id.
+    /// ## Examples
+    /// ```
+    /// client.votes(668701133069352961).await.unwrap();
+    /// ```
+    pub async fn votes(&self, bot_id: impl Into<Snowflake>) -> Option<Vec<Snowflake>> {
+        let bot_id = bot_id.into();
+        let url = format!("{}/bots/{}/votes", self.base_url, bot_id);
+        let res = self.get_json::<Vec<PartialJsonUser>>(&url).await?;
+
+        Some(
+            res.into_iter()
+                .map(|u| u.id)
+                .collect()
+        )
+    }
+
+
+    /// Gets the votes for your own bot and returns the users who aren't in `previous`, such as the
+    /// voters from the last time this was called. `VoterDiff` keeps track of them for you.
+    /// ## Examples
+    /// ```
+    /// let new = client.new_voters_since(&previous).await.unwrap();
+    /// ```
+    pub async fn new_voters_since(&self, previous: &HashSet<Snowflake>) -> Option<Vec<Snowflake>> {
+        let votes = self.my_votes().await?;
+        Some(voters::new_voters(&votes, previous))
+    }
+
+
+    /// Checks the votes for your own bot every `interval` and yields each user who voted since the last
+    /// check, for bots that can't receive webhooks because they have no public address. The votes
+    /// already listed when the stream starts aren't yielded. Checks count towards the rate limit like
+    /// any request, and failed ones are tried again at the next interval.
+    ///
+    /// top.gg only lists the last 1000 votes and doesn't say when they were made, so a webhook is more
+    /// reliable for popular bots, see `VoterDiff` for the details.
+    /// ## Examples
+    /// ```
+    /// use futures::StreamExt;
+    ///
+    /// let mut voters = Box::pin(client.poll_votes(Duration::from_secs(60)));
+    /// while let Some(user_id) = voters.next().await {
+    ///     println!("{} voted", user_id);
+    /// }
+    /// ```
+    pub fn poll_votes(&self, interval: Duration) -> impl futures::Stream<Item = Snowflake> + '_ {
+        voters::poll(self, interval)
+    }
+
+
+    /// A shortcut for checking if a user has voted for your own bot.
+    /// ## Examples
+    /// ```
+    /// let voted = client.voted_for_me(195512978634833920).await.unwrap();
+    /// ```
+    pub async fn voted_for_me(&self, user_id: impl Into<Snowflake>) -> Option<bool> {
+        self.voted(self.bot_id, user_id).await
+    }
+
+
+    /// Checks if a user has voted for the bot or not. Returns true if they have, false if they have not.
+    /// ## Examples
+    /// ```
+    /// let voted = client.voted(668701133069352961, 195512978634833920)
+    ///     .await
+    ///     .unwrap();
+    /// ```
+    pub async fn voted(&self, bot_id: impl Into<Snowflake>, user_id: impl Into<Snowflake>) -> Option<bool> {
+        let bot_id = bot_id.into();
+        let user_id = user_id.into();
+        if let Some(cache) = &self.voted_cache {
+            if let Some(voted) = cache.get(bot_id, user_id) {
+                return Some(voted);
+            }
+            let voted = self.check_voted(bot_id, user_id).await?;
+            cache.insert(bot_id, user_id, voted);
+            return Some(voted);
+        }
+        self.check_voted(bot_id, user_id).await
+    }
+
+
+    async fn check_voted(&self, bot_id: Snowflake, user_id: Snowflake) -> Option<bool> {
+        let url = format!("{}/bots/{}/check?userId={}", self.base_url, bot_id, user_id);
+        let res = self.get_json::<CheckVote>(&url).await?;
+
+        if res.voted == 0 {
+            return Some(false);
+        } else {
+            return Some(true);
+        }
+    }
+
+
+    /// Gets the info for a server listed on top.gg.
+    /// ## Examples
+    /// ```
+    /// let server = client.server(264445053596991498).await.unwrap();
+    /// println!("{} has {} votes this month", server.name, server.monthly_points);
+    /// ```
+    pub async fn server(&self, guild_id: impl Into<Snowflake>) -> Option<Server> {
+        let url = format!("{}/servers/{}", self.base_url, guild_id.into());
+        let res = self.get_json::<JsonServer>(&url).await?;
+
+        Some(Server::from(res))
+    }
+
+
+    /// Gets the IDs of the users that have voted for the server.
+    pub async fn server_votes(&self, guild_id: impl Into<Snowflake>) -> Option<Vec<Snowflake>> {
+        let url = format!("{}/servers/{}/votes", self.base_url, guild_id.into());
+        let res = self.get_json::<Vec<PartialJsonUser>>(&url).await?;
+
+        Some(res.into_iter().map(|u| u.id).collect())
+    }
+
+
+    /// Checks if a user has voted for the server.
+    /// ## Examples
+    /// ```
+    /// let voted = client.server_voted(264445053596991498, 195512978634833920).await.unwrap();
+    /// ```
+    pub async fn server_voted(&self, guild_id: impl Into<Snowflake>, user_id: impl Into<Snowflake>) -> Option<bool> {
+        let url = format!("{}/servers/{}/check?userId={}", self.base_url, guild_id.into(), user_id.into());
+        let res = self.get_json::<CheckVote>(&url).await?;
+
+        Some(res.voted != 0)
+    }
+
+
+    /// Checks if it's currently the weekend on top.gg, when every vote counts twice.
+    /// ## Examples
+    /// ```
+    /// let weekend = client.is_weekend().await.unwrap();
+    /// ```
+    pub async fn is_weekend(&self) -> Option<bool> {
+        let url = format!("{}/weekend", self.base_url);
+        let res = self.get_json::<Weekend>(&url).await?;
+
+        Some(res.is_weekend)
+    }
+
+
+    /// How many points a vote made right now is worth, 2 on weekends and 1 otherwise. For a vote
+    /// received by the webhook use `Webhook::vote_value` instead, which doesn't need a request.
+    /// ## Examples
+    /// ```
+    /// let points = client.vote_value().await.unwrap();
+    /// ```
+    pub async fn vote_value(&self) -> Option<u32> {
+        self.is_weekend().await.map(crate::webhook::vote_value)
+    }
+
+
+    /// A shortcut for getting the bot stats of the bot that created the client.
+    /// ## Examples
+    /// ```
+    /// let stats = client.my_bot_stats().await.unwrap();
+    /// ```
+    pub async fn my_bot_stats(&self) -> Option<BotStats> {
+        self.get_bot_stats(self.bot_id).await
+    }
+
+
+    /// Gets the 'stats' of the bot, this includes the server count, shard count, and shards (servers per shard).
+    /// ## Examples
+    /// ```
+    /// client.get_bot_stats(668701133069352961).await.unwrap();
+    /// ```
+    pub async fn get_bot_stats(&self, bot_id: impl Into<Snowflake>) -> Option<BotStats> {
+        let bot_id = bot_id.into();
+        let url = format!("{}/bots/{}/stats", self.base_url, bot_id);
+        let res = self.get_json::<BotStats>(&url).await?;
+
+        Some(res)
+    }
+
+
+    /// Gets the info for a bot as the JSON top.gg sent, for fields that `Bot` doesn't have yet.
+    /// ## Examples
+    /// ```
+    /// let bot = client.bot_raw(668701133069352961).await.unwrap();
+    /// let banner = bot["bannerUrl"].as_str();
+    /// ```
+    pub async fn bot_raw(&self, bot_id: impl Into<Snowflake>) -> Option<serde_json::Value> {
+        let bot_id = bot_id.into();
+        let url = format!("{}/bots/{}", self.base_url, bot_id);
+        self.get_json(&url).await
+    }
+
+
+    /// Gets the info for a user as the JSON top.gg sent, for fields that `User` doesn't have yet.
+    pub async fn user_raw(&self, user_id: impl Into<Snowflake>) -> Option<serde_json::Value> {
+        let user_id = user_id.into();
+        let url = format!("{}/users/{}", self.base_url, user_id);
+        self.get_json(&url).await
+    }
+
+
+    /// Gets the users who voted for a bot as the JSON top.gg sent, which has more than just their IDs.
+    pub async fn votes_raw(&self, bot_id: impl Into<Snowflake>) -> Option<serde_json::Value> {
+        let bot_id = bot_id.into();
+        let url = format!("{}/bots/{}/votes", self.base_url, bot_id);
+        self.get_json(&url).await
+    }
+
+
+    /// Gets the stats of a bot as the JSON top.gg sent.
+    pub async fn bot_stats_raw(&self, bot_id: impl Into<Snowflake>) -> Option<serde_json::Value> {
+        let bot_id = bot_id.into();
+        let url = format!("{}/bots/{}/stats", self.base_url, bot_id);
+        self.get_json(&url).await
+    }
+
+    
+    /// This posts the stats for your bot. Useful if you want to update the server count on your top.gg bot page. The stats are checked before being sent, see `StatsUpdate` for the kinds of update that can be posted. If top.gg refuses the update the error it gave is returned as `TopggError::Api`.
+    /// ## Examples
+    /// ```
+    /// use topgg::StatsUpdate;
+    ///
+    /// client.post_bot_stats(StatsUpdate::shards(vec![142, 532, 304])).await;
+    /// client.post_bot_stats(StatsUpdate::shard(0, 142, 3)).await;
+    /// client.post_bot_stats(StatsUpdate::server_count(978).with_shard_count(3)).await;
+    /// ```
+    pub async fn post_bot_stats(&self, stats: StatsUpdate) -> Result<(), TopggError> {
+        stats.validate()?;
+        let url = format!("{}/bots/{}/stats", self.base_url, self.bot_id);
+        let res = self.send(self.client.post(&url).json(&stats)).await?;
+        check_response(res).await?;
+        Ok(())
+    }
+
+
+    /// Makes a GET request to any API route, for routes this crate doesn't have a method for yet. The
+    /// request is authorized and rate limited like every other, and `query` is added as the query string.
+    ///
+    /// ## Arguments
+    /// * `path` - The route after `https://top.gg/api`, such as `/bots/668701133069352961`
+    /// * `query` - Anything serializing to a map or list of pairs, like `&[("limit", 10)]`, or `&()` for none
+    ///
+    /// ## Examples
+    /// ```
+    /// let bots: serde_json::Value = client.get("/bots", &[("search", "music"), ("limit", "10")]).await?;
+    /// ```
+    pub async fn get<T: DeserializeOwned>(&self, path: &str, query: &(impl Serialize + ?Sized)) -> Result<T, TopggError> {
+        Ok(self.get_with_meta(path, query).await?.data)
+    }
+
+
+    /// Like `get`, but also returns the status, rate limit headers and latency of the response.
+    pub async fn get_with_meta<T: DeserializeOwned>(&self, path: &str, query: &(impl Serialize + ?Sized)) -> Result<WithMeta<T>, TopggError> {
+        let url = format!("{}{}", self.base_url, path);
+        self.request_with_meta(self.client.get(&url).query(query)).await
+    }
+
+
+    /// Gets the info for a bot along with the status, rate limit headers and latency of the response,
+    /// for monitoring how the bot uses the API.
+    /// ## Examples
+    /// ```
+    /// let res = client.bot_with_meta(668701133069352961).await?;
+    /// println!("{} took {:?}, {:?} requests left", res.data.username, res.meta.latency, res.meta.rate_limit_remaining);
+    /// ```
+    pub async fn bot_with_meta(&self, bot_id: impl Into<Snowflake>) -> Result<WithMeta<Bot>, TopggError> {
+        let bot_id = bot_id.into();
+        let url = format!("{}/bots/{}", self.base_url, bot_id);
+        let res = self.request_with_meta::<JsonBot>(self.client.get(&url)).await?;
+        Ok(res.map(Bot::from))
+    }
+
+
+    /// Gets the info for a user along with the status, rate limit headers and latency of the response.
+    pub async fn user_with_meta(&self, user_id: impl Into<Snowflake>) -> Result<WithMeta<User>, TopggError> {
+        let user_id = user_id.into();
+        let url = format!("{}/users/{}", self.base_url, user_id);
+        let res = self.request_with_meta::<JsonUser>(self.client.get(&url)).await?;
+        Ok(res.map(User::from))
+    }
+
+
+    async fn request_with_meta<T: DeserializeOwned>(&self, req: reqwest::RequestBuilder) -> Result<WithMeta<T>, TopggError> {
+        let (res, started) = match self.fail_fast() {
+            Some(res) => (res, Instant::now()),
+            None => {
+                let req = req.header("Authorization", self.token()).build()?;
+                self.rate_limit.wait(req.url().path()).await;
+                let started = Instant::now();
+                (self.execute(req).await?, started)
+            }
+        };
+        let status = res.status().as_u16();
+        let header = |name: &str| res.headers().get(name)?.to_str().ok()?.parse::<u64>().ok();
+        let rate_limit = header("x-ratelimit-limit");
+        let rate_limit_remaining = header("x-ratelimit-remaining");
+        let rate_limit_reset = header("x-ratelimit-reset");
+        let retry_after = header("retry-after");
+
+        let body = check_response(res).await?.bytes().await?;
+        let data = serde_json::from_slice(&body).map_err(TopggError::Decode)?;
+        Ok(WithMeta {
+            data,
+            meta: ResponseMeta {
+                status,
+                latency: started.elapsed(),
+                rate_limit,
+                rate_limit_remaining,
+                rate_limit_reset,
+                retry_after,
+            },
+        })
+    }
+
+
+    /// Makes a POST request with a JSON body to any API route, returning the JSON top.gg responded
+    /// with, or `Value::Null` if the response was empty. See `get`.
+    /// ## Examples
+    /// ```
+    /// client.post("/bots/stats", &serde_json::json!({ "server_count": 978 })).await?;
+    /// ```
+    pub async fn post(&self, path: &str, body: &(impl Serialize + ?Sized)) -> Result<serde_json::Value, TopggError> {
+        let url = format!("{}{}", self.base_url, path);
+        let res = self.send(self.client.post(&url).json(body)).await?;
+        let body = check_response(res).await?.bytes().await?;
+        if body.is_empty() {
+            return Ok(serde_json::Value::Null);
+        }
+        serde_json::from_slice(&body).map_err(TopggError::Decode)
+    }
+
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Option<T> {
+        let body = self.get_body(url).await?;
+        serde_json::from_slice(&body).ok()
+    }
+
+
+    /// Gets the body at `url`. If the same URL is already being requested the response to that
+    /// request is shared instead of sending another one.
+    async fn get_body(&self, url: &str) -> Option<Arc<[u8]>> {
+        let waiting = {
+            let mut in_flight = self.in_flight.waiting.lock().unwrap();
+            match in_flight.get_mut(url) {
+                Some(waiters) => {
+                    let (send, read) = oneshot::channel();
+                    waiters.push(send);
+                    Some(read)
+                }
+                None => {
+                    in_flight.insert(url.to_string(), Vec::new());
+                    None
+                }
+            }
+        };
+
+        if let Some(read) = waiting {
+            match read.await {
+                Ok(body) => return body,
+                // The task making the request was cancelled, so make it again.
+                Err(_) => return self.request_body(url).await,
+            }
+        }
+
+        let mut leader = Leader {
+            in_flight: &self.in_flight,
+            url,
+            body: None,
+        };
+        let body = self.request_body(url).await;
+        leader.body = Some(body.clone());
+        body
+    }
+
+
+    async fn request_body(&self, url: &str) -> Option<Arc<[u8]>> {
+        let body = self.send(self.client.get(url))
+            .await
+            .ok()?
+            .bytes()
+            .await
+            .ok()?;
+        Some(Arc::from(&body[..]))
+    }
+
+
+    /// Sends the request once the rate limit allows it.
+    async fn send(&self, req: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+        if let Some(res) = self.fail_fast() {
+            return Ok(res);
+        }
+        let req = req.header("Authorization", self.token()).build()?;
+        self.rate_limit.wait(req.url().path()).await;
+        self.execute(req).await
+    }
+
+
+    /// The response to give instead of sending a request while the circuit breaker is open.
+    fn fail_fast(&self) -> Option<reqwest::Response> {
+        match &self.breaker {
+            Some(breaker) if !breaker.allow() => Some(local_response(503, "requests to top.gg are paused after repeated failures")),
+            _ => None,
+        }
+    }
+
+
+    /// Sends the request, which already has the token, without waiting for the rate limit. With the `otel` feature
+    /// each request gets an OpenTelemetry client span, which is a child of the context the client's
+    /// future runs in, so attach a parent with `opentelemetry::trace::FutureExt::with_context`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "topgg_request", skip_all))]
+    async fn execute(&self, mut req: reqwest::Request) -> reqwest::Result<reqwest::Response> {
+        for middleware in &self.middleware {
+            middleware.on_request(&mut req).await;
+        }
+        let summary = RequestSummary {
+            method: req.method().clone(),
+            url: req.url().clone(),
+        };
+        event!(tracing::Level::DEBUG, method = %req.method(), url = %req.url(), "sending request");
+        #[cfg(feature = "otel")]
+        let span = otel::start(&mut req);
+        #[cfg(any(feature = "tracing", feature = "metrics"))]
+        let started = Instant::now();
+
+        #[cfg(feature = "vcr")]
+        let res = match &self.cassette {
+            Some(cassette) => cassette.execute(&self.client, req).await,
+            None => self.client.execute(req).await,
+        };
+        #[cfg(not(feature = "vcr"))]
+        let res = self.client.execute(req).await;
+        let res = match res {
+            Ok(mut res) => {
+                for middleware in &self.middleware {
+                    middleware.on_response(&summary, &mut res).await;
+                }
+                Ok(res)
+            }
+            Err(e) => {
+                for middleware in &self.middleware {
+                    middleware.on_error(&summary, &e).await;
+                }
+                Err(e)
+            }
+        };
+        if let Ok(res) = &res {
+            if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = res
+                    .headers()
+                    .get("retry-after")
+                    .and_then(|value| value.to_str().ok()?.parse::<u64>().ok())
+                    .map(Duration::from_secs);
+                self.rate_limit.pause(retry_after);
+            }
+        }
+        if let Some(breaker) = &self.breaker {
+            breaker.record(matches!(&res, Ok(res) if !res.status().is_server_error()));
+        }
+        #[cfg(feature = "metrics")]
+        telemetry::record_response(&summary.url, res.as_ref().ok().map(|res| res.status().as_u16()), started.elapsed());
+        #[cfg(feature = "otel")]
+        span.end(res.as_ref().map(|res| res.status().as_u16()).map_err(|e| e.to_string()));
+        #[cfg(feature = "tracing")]
+        match &res {
+            Ok(res) if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                tracing::warn!(status = res.status().as_u16(), "rate limited by top.gg");
+            }
+            Ok(res) => {
+                tracing::debug!(status = res.status().as_u16(), elapsed_ms = started.elapsed().as_millis() as u64, "received response");
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "request failed");
+            }
+        }
+        res
+    }
+
+
+    fn token(&self) -> &str {
+        expose_token(&self.token)
+    }
+}
+impl std::fmt::Debug for Topgg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The token is left out so logging the client doesn't leak it.
+        f.debug_struct("Topgg")
+            .field("bot_id", &self.bot_id)
+            .field("token", &"[redacted]")
+            .finish_non_exhaustive()
+    }
+}
+
+
+/// A response from top.gg along with details about how it went, from the `_with_meta` methods.
+#[derive(Debug, Clone)]
+pub struct WithMeta<T> {
+    pub data: T,
+    pub meta: ResponseMeta,
+}
+impl<T> WithMeta<T> {
+    /// Converts the data while keeping the details of the response.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> WithMeta<U> {
+        WithMeta {
+            data: f(self.data),
+            meta: self.meta,
+        }
+    }
+}
+
+
+/// Details about a response from top.gg. The rate limit fields are `None` when top.gg didn't send the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseMeta {
+    /// The HTTP status code.
+    pub status: u16,
+    /// How long it took from sending the request to reading the whole response, not counting any
+    /// wait for the client's own rate limiter.
+    pub latency: Duration,
+    /// How many requests can be made in the current window, from `X-RateLimit-Limit`.
+    pub rate_limit: Option<u64>,
+    /// How many requests are left in the current window, from `X-RateLimit-Remaining`.
+    pub rate_limit_remaining: Option<u64>,
+    /// When the current window ends, from `X-RateLimit-Reset`.
+    pub rate_limit_reset: Option<u64>,
+    /// How many seconds to wait before retrying after being rate limited, from `Retry-After`.
+    pub retry_after: Option<u64>,
+}
+
+
+/// The recent results of `Topgg::voted`, see `Topgg::cache_voted`.
+struct VotedCache {
+    ttl: Duration,
+    state: Mutex<VotedState>,
+}
+
+struct VotedState {
+    entries: HashMap<(Snowflake, Snowflake), (bool, Instant)>,
+    last_pruned: Instant,
+}
+
+impl VotedCache {
+    fn new(ttl: Duration) -> VotedCache {
+        VotedCache {
+            ttl,
+            state: Mutex::new(VotedState {
+                entries: HashMap::new(),
+                last_pruned: Instant::now(),
+            }),
+        }
+    }
+
+
+    fn get(&self, bot_id: Snowflake, user_id: Snowflake) -> Option<bool> {
+        let state = self.state.lock().unwrap();
+        match state.entries.get(&(bot_id, user_id)) {
+            Some((voted, at)) if at.elapsed() < self.ttl => Some(*voted),
+            _ => None,
+        }
+    }
+
+
+    fn insert(&self, bot_id: Snowflake, user_id: Snowflake, voted: bool) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if now.duration_since(state.last_pruned) >= self.ttl {
+            let ttl = self.ttl;
+            state.entries.retain(|_, (_, at)| now.duration_since(*at) < ttl);
+            state.last_pruned = now;
+        }
+        state.entries.insert((bot_id, user_id), (voted, now));
+    }
+}
+
+
+/// The GET requests being made, along with the callers waiting to share each one's response.
+#[derive(Default)]
+struct InFlight {
+    waiting: Mutex<HashMap<String, Vec<oneshot::Sender<Option<Arc<[u8]>>>>>>,
+}
+
+
+/// Held by the caller making a request that others are waiting on. When dropped it hands out the
+/// response, or if the request was cancelled before finishing it lets the waiters make their own.
+struct Leader<'a> {
+    in_flight: &'a InFlight,
+    url: &'a str,
+    body: Option<Option<Arc<[u8]>>>,
+}
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        let waiters = self.in_flight.waiting.lock().unwrap().remove(self.url).unwrap_or_default();
+        if let Some(body) = self.body.take() {
+            for waiter in waiters {
+                let _ = waiter.send(body.clone());
+            }
+        }
+    }
+}
+
+
+
+/// A response in top.gg's error format made up by the client, for requests it answers itself
+/// instead of sending.
+pub(crate) fn local_response(status: u16, message: &str) -> reqwest::Response {
+    let body = serde_json::json!({ "error": message }).to_string();
+    let mut res = http::Response::new(body);
+    *res.status_mut() = http::StatusCode::from_u16(status).unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+    res.headers_mut().insert(http::header::CONTENT_TYPE, http::HeaderValue::from_static("application/json"));
+    reqwest::Response::from(res)
+}
+
+
+/// Turns an unsuccessful response into a `TopggError::Api`, using the error message top.gg sent if it has one.
+pub(crate) async fn check_response(res: reqwest::Response) -> Result<reqwest::Response, TopggError> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+
+    let body = res.text().await?;
+    let message = match serde_json::from_str::<ApiError>(&body) {
+        Ok(e) => e.error,
+        Err(_) if body.is_empty() => status.canonical_reason().unwrap_or("unknown error").to_string(),
+        Err(_) => body,
+    };
+    Err(TopggError::Api {
+        status: status.as_u16(),
+        message,
+    })
+}
+
+
+
+/// The requests that can be made to top.gg, implemented by `Topgg`. Code which takes an
+/// `impl TopggApi` (or a `&dyn TopggApi`) instead of a `Topgg` can be given a fake client in tests,
+/// such as `testing::MockTopgg`.
+/// ## Examples
+/// ```
+/// use topgg::TopggApi;
+///
+/// async fn can_use_premium(api: &impl TopggApi, user_id: topgg::Snowflake) -> bool {
+///     api.voted_for_me(user_id).await.unwrap_or(false)
+/// }
+/// ```
+#[async_trait]
+pub trait TopggApi: Send + Sync {
+    /// The ID of the bot the client was made for.
+    fn bot_id(&self) -> Snowflake;
+
+    /// See `Topgg::bot`.
+    async fn bot(&self, bot_id: Snowflake) -> Option<Bot>;
+
+    /// See `Topgg::user`.
+    async fn user(&self, user_id: Snowflake) -> Option<User>;
+
+    /// See `Topgg::votes`.
+    async fn votes(&self, bot_id: Snowflake) -> Option<Vec<Snowflake>>;
+
+    /// See `Topgg::voted`.
+    async fn voted(&self, bot_id: Snowflake, user_id: Snowflake) -> Option<bool>;
+
+    /// See `Topgg::is_weekend`.
+    async fn is_weekend(&self) -> Option<bool>;
+
+    /// See `Topgg::get_bot_stats`.
+    async fn get_bot_stats(&self, bot_id: Snowflake) -> Option<BotStats>;
+
+    /// See `Topgg::post_bot_stats`.
+    async fn post_bot_stats(&self, stats: StatsUpdate) -> Result<(), TopggError>;
+
+    /// See `Topgg::my_bot`.
+    async fn my_bot(&self) -> Option<Bot> {
+        self.bot(self.bot_id()).await
+    }
+
+    /// See `Topgg::my_votes`.
+    async fn my_votes(&self) -> Option<Vec<Snowflake>> {
+        self.votes(self.bot_id()).await
+    }
+
+    /// See `Topgg::voted_for_me`.
+    async fn voted_for_me(&self, user_id: Snowflake) -> Option<bool> {
+        self.voted(self.bot_id(), user_id).await
+    }
+
+    /// See `Topgg::my_bot_stats`.
+    async fn my_bot_stats(&self) -> Option<BotStats> {
+        self.get_bot_stats(self.bot_id()).await
+    }
+}
+
+#[async_trait]
+impl TopggApi for Topgg {
+    fn bot_id(&self) -> Snowflake {
+        self.bot_id
+    }
+
+    async fn bot(&self, bot_id: Snowflake) -> Option<Bot> {
+        Topgg::bot(self, bot_id).await
+    }
+
+    async fn user(&self, user_id: Snowflake) -> Option<User> {
+        Topgg::user(self, user_id).await
+    }
+
+    async fn votes(&self, bot_id: Snowflake) -> Option<Vec<Snowflake>> {
+        Topgg::votes(self, bot_id).await
+    }
+
+    async fn voted(&self, bot_id: Snowflake, user_id: Snowflake) -> Option<bool> {
+        Topgg::voted(self, bot_id, user_id).await
+    }
+
+    async fn is_weekend(&self) -> Option<bool> {
+        Topgg::is_weekend(self).await
+    }
+
+    async fn get_bot_stats(&self, bot_id: Snowflake) -> Option<BotStats> {
+        Topgg::get_bot_stats(self, bot_id).await
+    }
+
+    async fn post_bot_stats(&self, stats: StatsUpdate) -> Result<(), TopggError> {
+        Topgg::post_bot_stats(self, stats).await
+    }
+}
+
+
+
+/// A bot list that your bot's stats can be posted to. `Topgg` implements this, and other lists can be
+/// implemented by hand so that `MultiPoster` can post to all of them at once.
+#[async_trait]
+pub trait BotList: Send + Sync {
+    /// The name of the list, used to tell the results of a `MultiPoster` apart.
+    fn name(&self) -> &str;
+
+    /// Posts the server count, and optionally the shard count, of your bot to the list.
+    async fn post_stats(&self, server_count: u32, shard_count: Option<u32>) -> Result<(), TopggError>;
+}
+
+#[async_trait]
+impl BotList for Topgg {
+    fn name(&self) -> &str {
+        "top.gg"
+    }
+
+    async fn post_stats(&self, server_count: u32, shard_count: Option<u32>) -> Result<(), TopggError> {
+        let mut stats = StatsUpdate::server_count(server_count);
+        if let Some(shard_count) = shard_count {
+            stats = stats.with_shard_count(shard_count);
+        }
+        self.post_bot_stats(stats).await
+    }
+}
+
+
+/// Posts your bot's stats to several bot lists concurrently.
+pub struct MultiPoster {
+    lists: Vec<Box<dyn BotList>>,
+}
+impl MultiPoster {
+    /// Returns a new poster with no lists configured.
+    /// ## Examples
+    /// ```
+    /// let poster = topgg::MultiPoster::new()
+    ///     .add(topgg::Topgg::new(bot_id, token));
+    /// ```
+    pub fn new() -> MultiPoster {
+        MultiPoster {
+            lists: Vec::new(),
+        }
+    }
+
+
+    /// Adds a list that stats will be posted to.
+    pub fn add<L: BotList + 'static>(mut self, list: L) -> MultiPoster {
+        self.lists.push(Box::new(list));
+        self
+    }
+
+
+    /// Posts the stats to every configured list at the same time. Returns the result for each list
+    /// alongside its name, in the order the lists were added.
+    /// ## Examples
+    /// ```
+    /// for (name, result) in poster.post_stats(142, None).await {
+    ///     if let Err(e) = result {
+    ///         println!("posting to {} failed: {}", name, e);
+    ///     }
+    /// }
+    /// ```
+    pub async fn post_stats(&self, server_count: u32, shard_count: Option<u32>) -> Vec<(String, Result<(), TopggError>)> {
+        let posts = self.lists.iter().map(|list| async move {
+            (list.name().to_string(), list.post_stats(server_count, shard_count).await)
+        });
+        future::join_all(posts).await
+    }
+}
+impl Default for MultiPoster {
+    fn default() -> MultiPoster {
+        MultiPoster::new()
+    }
+}
+
+
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct JsonBot {
+    id: Snowflake,
+    username: String,
+    discriminator: String,
+    avatar: Option<String>,
+    defAvatar: String, 
+    lib: String,
+    prefix: String,
+    shortdesc: String,
+    longdesc: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    website: Option<String>,
+    support: Option<String>,
+    github: Option<String>,
+    #[serde(default)]
+    owners: Vec<Snowflake>,
+    #[serde(default)]
+    guilds: Vec<Snowflake>,
+    invite: Option<String>,
+    #[cfg(feature = "chrono")]
+    date: chrono::DateTime<chrono::Utc>,
+    #[cfg(not(feature = "chrono"))]
+    date: String,
+    certifiedBot: bool,
+    vanity: Option<String>,
+    points: u64,
+    monthlyPoints: u64,
+    #[serde(default, deserialize_with = "crate::id::deserialize_option")]
+    donatebotguildid: Option<Snowflake>
+}
+impl From<JsonBot> for Bot {
+    fn from(res: JsonBot) -> Bot {
+        Bot {
+            id: res.id,
+            username: res.username,
+            discriminator: res.discriminator,
+            avatar: res.avatar,
+            def_avatar: res.defAvatar,
+            lib: res.lib,
+            prefix: res.prefix,
+            short_desc: res.shortdesc,
+            long_desc: res.longdesc,
+            tags: res.tags,
+            website: res.website,
+            support: res.support,
+            github: res.github,
+            owners: res.owners,
+            guilds: res.guilds,
+            invite: res.invite,
+            date: res.date,
+            certified_bot: res.certifiedBot,
+            vanity: res.vanity,
+            points: res.points,
+            monthly_points: res.monthlyPoints,
+            donate_bot_guild_id: res.donatebotguildid
+        }
+    }
+}
+impl From<Bot> for JsonBot {
+    fn from(bot: Bot) -> JsonBot {
+        JsonBot {
+            id: bot.id,
+            username: bot.username,
+            discriminator: bot.discriminator,
+            avatar: bot.avatar,
+            defAvatar: bot.def_avatar,
+            lib: bot.lib,
+            prefix: bot.prefix,
+            shortdesc: bot.short_desc,
+            longdesc: bot.long_desc,
+            tags: bot.tags,
+            website: bot.website,
+            support: bot.support,
+            github: bot.github,
+            owners: bot.owners,
+            guilds: bot.guilds,
+            invite: bot.invite,
+            date: bot.date,
+            certifiedBot: bot.certified_bot,
+            vanity: bot.vanity,
+            points: bot.points,
+            monthlyPoints: bot.monthly_points,
+            donatebotguildid: bot.donate_bot_guild_id
+        }
+    }
+}
+
+
+#[allow(non_snake_case)]
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct JsonUser {
+    id: Snowflake,
+    username: String,
+    discriminator: String,
+    avatar: Option<String>,
+    defAvatar: String,
+    bio: Option<String>,
+    banner: Option<String>,
+    #[serde(default)]
+    social: Social,
+    color: Option<String>,
+    supporter: bool,
+    certifiedDev: bool,
+    r#mod: bool,
+    webMod: bool,
+    admin: bool,
+}
+impl From<JsonUser> for User {
+    fn from(res: JsonUser) -> User {
+        User {
+            id: res.id,
+            username: res.username,
+            discriminator: res.discriminator,
+            avatar:res.avatar,
+            def_avatar: res.defAvatar,
+            bio: res.bio,
+            banner: res.banner,
+            social: res.social,
+            color: res.color,
+            supporter: res.supporter,
+            certified_dev: res.certifiedDev,
+            moderator: res.r#mod,
+            web_moderator: res.webMod,
+            admin: res.admin,
+        }
+    }
+}
+impl From<User> for JsonUser {
+    fn from(user: User) -> JsonUser {
+        JsonUser {
+            id: user.id,
+            username: user.username,
+            discriminator: user.discriminator,
+            avatar: user.avatar,
+            defAvatar: user.def_avatar,
+            bio: user.bio,
+            banner: user.banner,
+            social: user.social,
+            color: user.color,
+            supporter: user.supporter,
+            certifiedDev: user.certified_dev,
+            r#mod: user.moderator,
+            webMod: user.web_moderator,
+            admin: user.admin,
+        }
+    }
+}
+
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Debug)]
+struct JsonServer {
+    id: Snowflake,
+    name: String,
+    icon: Option<String>,
+    #[serde(default)]
+    shortdesc: String,
+    longdesc: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    website: Option<String>,
+    invite: Option<String>,
+    vanity: Option<String>,
+    #[serde(default)]
+    points: u64,
+    #[serde(default)]
+    monthlyPoints: u64,
+}
+impl From<JsonServer> for Server {
+    fn from(res: JsonServer) -> Server {
+        Server {
+            id: res.id,
+            name: res.name,
+            icon: res.icon,
+            short_desc: res.shortdesc,
+            long_desc: res.longdesc,
+            tags: res.tags,
+            website: res.website,
+            invite: res.invite,
+            vanity: res.vanity,
+            points: res.points,
+            monthly_points: res.monthlyPoints,
+        }
+    }
+}
+
+
+#[derive(Deserialize, Debug)]
+struct PartialJsonUser {
+    id: Snowflake,
+    username: String,
+    discriminator: String,
+    avatar: Option<String>
+}
+
+
+#[derive(Deserialize, Debug)]
+struct ApiError {
+    error: String
+}
+
+
+#[derive(Deserialize, Debug)]
+struct CheckVote {
+    voted: i8
+}
+
+
+#[derive(Deserialize, Debug)]
+struct Weekend {
+    is_weekend: bool
+}
+
+
+
+/// The stats to post for your bot with `Topgg::post_bot_stats`. Each constructor describes one valid kind of update,
+/// so combinations top.gg would reject can't be built by accident.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct StatsUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    server_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shards: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shard_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shard_count: Option<u32>,
+}
+impl StatsUpdate {
+    /// The total number of servers your bot is in.
+    pub fn server_count(server_count: u32) -> StatsUpdate {
+        StatsUpdate {
+            server_count: Some(server_count),
+            shards: None,
+            shard_id: None,
+            shard_count: None,
+        }
+    }
+
+
+    /// The number of servers per shard, indexed by shard ID. top.gg works out the total from this.
+    pub fn shards(shards: Vec<u32>) -> StatsUpdate {
+        StatsUpdate {
+            server_count: None,
+            shards: Some(shards),
+            shard_id: None,
+            shard_count: None,
+        }
+    }
+
+
+    /// The number of servers a single shard is in, for bots that post from every shard separately.
+    ///
+    /// ## Arguments
+    /// * `shard_id` - The ID of the shard posting
+    /// * `server_count` - The number of servers that shard is in
+    /// * `shard_count` - The total number of shards your bot has
+    pub fn shard(shard_id: u32, server_count: u32, shard_count: u32) -> StatsUpdate {
+        StatsUpdate {
+            server_count: Some(server_count),
+            shards: None,
+            shard_id: Some(shard_id),
+            shard_count: Some(shard_count),
+        }
+    }
+
+
+    /// Also tells top.gg the total number of shards your bot has.
+    pub fn with_shard_count(mut self, shard_count: u32) -> StatsUpdate {
+        self.shard_count = Some(shard_count);
+        self
+    }
+
+
+    /// Checks the update makes sense before it is sent.
+    pub fn validate(&self) -> Result<(), TopggError> {
+        if let Some(shards) = &self.shards {
+            if shards.is_empty() {
+                return Err(TopggError::InvalidStats("shards can't be empty"));
+            }
+        }
+        if self.shard_count == Some(0) {
+            return Err(TopggError::InvalidStats("shard_count can't be 0"));
+        }
+        if let (Some(shard_id), Some(shard_count)) = (self.shard_id, self.shard_count) {
+            if shard_id >= shard_count {
+                return Err(TopggError::InvalidStats("shard_id must be less than shard_count"));
+            }
+        }
+        Ok(())
+    }
+}
+
+
+
+/// The errors that can happen when using the client.
+#[derive(Debug)]
+pub enum TopggError {
+    /// The request failed to send or its response couldn't be read.
+    Http(reqwest::Error),
+    /// The stats were rejected before being sent, with the reason why.
+    InvalidStats(&'static str),
+    /// top.gg responded with an error.
+    Api {
+        status: u16,
+        message: String,
+    },
+    /// The response wasn't the JSON that was expected.
+    Decode(serde_json::Error),
+    /// The client was configured with something that can't be used.
+    Config(ConfigError),
+}
+impl std::fmt::Display for TopggError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TopggError::Http(e) => write!(f, "http error: {}", e),
+            TopggError::InvalidStats(reason) => write!(f, "invalid stats: {}", reason),
+            TopggError::Api { status, message } => write!(f, "top.gg responded with {}: {}", status, message),
+            TopggError::Decode(e) => write!(f, "unexpected response: {}", e),
+            TopggError::Config(e) => write!(f, "invalid configuration: {}", e),
+        }
+    }
+}
+impl std::error::Error for TopggError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TopggError::Http(e) => Some(e),
+            TopggError::Decode(e) => Some(e),
+            TopggError::Config(e) => Some(e),
+            TopggError::InvalidStats(_) | TopggError::Api { .. } => None,
+        }
+    }
+}
+impl From<reqwest::Error> for TopggError {
+    fn from(e: reqwest::Error) -> TopggError {
+        TopggError::Http(e)
+    }
+}
+impl From<ConfigError> for TopggError {
+    fn from(e: ConfigError) -> TopggError {
+        TopggError::Config(e)
+    }
+}
+
+
+/// Why a client couldn't be made from its configuration, such as in `Topgg::from_env`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The environment variable with this name isn't set or is empty.
+    Missing(&'static str),
+    /// The value given for `name` couldn't be used, with the reason why.
+    Invalid {
+        name: &'static str,
+        reason: String,
+    },
+}
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Missing(name) => write!(f, "{} isn't set", name),
+            ConfigError::Invalid { name, reason } => write!(f, "invalid {}: {}", name, reason),
+        }
+    }
+}
+impl std::error::Error for ConfigError {}
+
+
+/// Reads the bot ID from the payload of a top.gg token, which looks like `{"id":"264811613708746752","bot":true,...}`.
+fn token_bot_id(token: &str) -> Result<Snowflake, ConfigError> {
+    #[derive(Deserialize)]
+    struct Claims {
+        id: Snowflake,
+    }
+
+    let invalid = |reason: &str| ConfigError::Invalid {
+        name: "token",
+        reason: reason.to_string(),
+    };
+    let payload = token.split('.').nth(1).ok_or_else(|| invalid("not a JWT"))?;
+    let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|_| invalid("the payload isn't base64"))?;
+    let claims = serde_json::from_slice::<Claims>(&payload).map_err(|_| invalid("the payload has no bot ID"))?;
+    Ok(claims.id)
+}
+
+
+/// The bot ID and token from `TOPGG_BOT_ID` and `TOPGG_TOKEN`, reading the ID from the token if
+/// `TOPGG_BOT_ID` isn't set.
+fn env_config() -> Result<(Snowflake, String), ConfigError> {
+    let token = env_var("TOPGG_TOKEN")?.trim().to_string();
+    let bot_id = match env_var("TOPGG_BOT_ID") {
+        Ok(bot_id) => bot_id.trim().parse::<Snowflake>().map_err(|_| ConfigError::Invalid {
+            name: "TOPGG_BOT_ID",
+            reason: format!("{:?} isn't a discord ID", bot_id),
+        })?,
+        Err(ConfigError::Missing(_)) => token_bot_id(&token)?,
+        Err(e) => return Err(e),
+    };
+    Ok((bot_id, token))
+}
+
+
+/// Reads an environment variable, treating one that's empty as missing.
+fn env_var(name: &'static str) -> Result<String, ConfigError> {
+    match std::env::var(name) {
+        Ok(value) if !value.trim().is_empty() => Ok(value),
+        Ok(_) | Err(std::env::VarError::NotPresent) => Err(ConfigError::Missing(name)),
+        Err(std::env::VarError::NotUnicode(_)) => Err(ConfigError::Invalid {
+            name,
+            reason: "not valid unicode".to_string(),
+        }),
+    }
+}
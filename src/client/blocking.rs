@@ -0,0 +1,261 @@
+//! A client which blocks the thread until top.gg responds, for programs that don't run an async
+//! runtime, like command line tools and cron jobs. It has the same methods as the async `Topgg`
+//! without the `.await`s, and keeps to the same rate limits. Needs the `blocking` feature.
+//!
+//! The extras of the async client, like caching, middleware and the circuit breaker, aren't available.
+//! Don't use it from inside an async runtime, where it would block the runtime's threads.
+//! ## Examples
+//! ```
+//! let client = topgg::blocking::Topgg::from_env()?;
+//! client.post_bot_stats(topgg::StatsUpdate::server_count(server_count))?;
+//! let voted = client.voted_for_me(user_id).unwrap_or(false);
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::{
+    env_config, expose_token, secure_token, token_bot_id, ApiError, CheckVote, ConfigError, JsonBot, JsonServer,
+    JsonUser, PartialJsonUser, RateLimit, StatsUpdate, Token, TopggError, Weekend, BASE_URL, USER_AGENT,
+};
+use crate::{Bot, BotStats, Server, Snowflake, User};
+
+
+
+/// The top.gg API client, blocking the thread for each request.
+pub struct Topgg {
+    bot_id: Snowflake,
+    token: Token,
+    base_url: String,
+    client: reqwest::blocking::Client,
+    rate_limit: Arc<RateLimit>,
+}
+impl Topgg {
+    /// Returns a new client.
+    ///
+    /// ## Arguments
+    /// * `bot_id` - The ID of your bot
+    /// * `token` - The top.gg token for that (or another valid) bot
+    pub fn new(bot_id: impl Into<Snowflake>, token: String) -> Topgg {
+        let client = reqwest::blocking::Client::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .expect("the HTTP client couldn't be set up");
+        Topgg {
+            bot_id: bot_id.into(),
+            token: secure_token(token),
+            base_url: BASE_URL.to_string(),
+            client,
+            rate_limit: Arc::new(RateLimit::new()),
+        }
+    }
+
+
+    /// Returns a new client configured from the environment, like `topgg::Topgg::from_env`.
+    pub fn from_env() -> Result<Topgg, ConfigError> {
+        let (bot_id, token) = env_config()?;
+        Ok(Topgg::new(bot_id, token))
+    }
+
+
+    /// Returns a new client for the bot the token belongs to, like `topgg::Topgg::from_token`.
+    pub fn from_token(token: String) -> Result<Topgg, ConfigError> {
+        let bot_id = token_bot_id(&token)?;
+        Ok(Topgg::new(bot_id, token))
+    }
+
+
+    /// Sends requests to `url` instead of `https://top.gg/api`.
+    pub fn base_url(mut self, url: impl Into<String>) -> Topgg {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+
+    /// A shortcut for getting the info for your own bot.
+    pub fn my_bot(&self) -> Option<Bot> {
+        self.bot(self.bot_id)
+    }
+
+
+    /// Gets the info for a bot.
+    /// ## Examples
+    /// ```
+    /// let bot_info = client.bot(668701133069352961).unwrap();
+    /// ```
+    pub fn bot(&self, bot_id: impl Into<Snowflake>) -> Option<Bot> {
+        let url = format!("{}/bots/{}", self.base_url, bot_id.into());
+        let res = self.get_json::<JsonBot>(&url)?;
+
+        Some(Bot::from(res))
+    }
+
+
+    /// Gets the info for a user.
+    pub fn user(&self, user_id: impl Into<Snowflake>) -> Option<User> {
+        let url = format!("{}/users/{}", self.base_url, user_id.into());
+        let res = self.get_json::<JsonUser>(&url)?;
+
+        Some(User::from(res))
+    }
+
+
+    /// A shortcut for getting the votes for your own bot.
+    pub fn my_votes(&self) -> Option<Vec<Snowflake>> {
+        self.votes(self.bot_id)
+    }
+
+
+    /// Gets the IDs of the users that have voted for the bot.
+    pub fn votes(&self, bot_id: impl Into<Snowflake>) -> Option<Vec<Snowflake>> {
+        let url = format!("{}/bots/{}/votes", self.base_url, bot_id.into());
+        let res = self.get_json::<Vec<PartialJsonUser>>(&url)?;
+
+        Some(res.into_iter().map(|u| u.id).collect())
+    }
+
+
+    /// A shortcut for checking if a user has voted for your own bot.
+    pub fn voted_for_me(&self, user_id: impl Into<Snowflake>) -> Option<bool> {
+        self.voted(self.bot_id, user_id)
+    }
+
+
+    /// Checks if a user has voted for the bot.
+    /// ## Examples
+    /// ```
+    /// let voted = client.voted(668701133069352961, 195512978634833920).unwrap();
+    /// ```
+    pub fn voted(&self, bot_id: impl Into<Snowflake>, user_id: impl Into<Snowflake>) -> Option<bool> {
+        let url = format!("{}/bots/{}/check?userId={}", self.base_url, bot_id.into(), user_id.into());
+        let res = self.get_json::<CheckVote>(&url)?;
+
+        Some(res.voted != 0)
+    }
+
+
+    /// Gets the info for a server listed on top.gg.
+    pub fn server(&self, guild_id: impl Into<Snowflake>) -> Option<Server> {
+        let url = format!("{}/servers/{}", self.base_url, guild_id.into());
+        let res = self.get_json::<JsonServer>(&url)?;
+
+        Some(Server::from(res))
+    }
+
+
+    /// Gets the IDs of the users that have voted for the server.
+    pub fn server_votes(&self, guild_id: impl Into<Snowflake>) -> Option<Vec<Snowflake>> {
+        let url = format!("{}/servers/{}/votes", self.base_url, guild_id.into());
+        let res = self.get_json::<Vec<PartialJsonUser>>(&url)?;
+
+        Some(res.into_iter().map(|u| u.id).collect())
+    }
+
+
+    /// Checks if a user has voted for the server.
+    pub fn server_voted(&self, guild_id: impl Into<Snowflake>, user_id: impl Into<Snowflake>) -> Option<bool> {
+        let url = format!("{}/servers/{}/check?userId={}", self.base_url, guild_id.into(), user_id.into());
+        let res = self.get_json::<CheckVote>(&url)?;
+
+        Some(res.voted != 0)
+    }
+
+
+    /// Checks if it's currently the weekend on top.gg, when every vote counts twice.
+    pub fn is_weekend(&self) -> Option<bool> {
+        let url = format!("{}/weekend", self.base_url);
+        let res = self.get_json::<Weekend>(&url)?;
+
+        Some(res.is_weekend)
+    }
+
+
+    /// A shortcut for getting the stats of your own bot.
+    pub fn my_bot_stats(&self) -> Option<BotStats> {
+        self.get_bot_stats(self.bot_id)
+    }
+
+
+    /// Gets the server count, shard count and servers per shard of the bot.
+    pub fn get_bot_stats(&self, bot_id: impl Into<Snowflake>) -> Option<BotStats> {
+        let url = format!("{}/bots/{}/stats", self.base_url, bot_id.into());
+        self.get_json::<BotStats>(&url)
+    }
+
+
+    /// Posts the stats for your bot, see `topgg::Topgg::post_bot_stats`.
+    /// ## Examples
+    /// ```
+    /// client.post_bot_stats(topgg::StatsUpdate::server_count(978))?;
+    /// ```
+    pub fn post_bot_stats(&self, stats: StatsUpdate) -> Result<(), TopggError> {
+        stats.validate()?;
+        let url = format!("{}/bots/{}/stats", self.base_url, self.bot_id);
+        let res = self.send(self.client.post(&url).json(&stats))?;
+        check_response(res)?;
+        Ok(())
+    }
+
+
+    /// Makes a GET request to any API route, like `topgg::Topgg::get`.
+    pub fn get<T: DeserializeOwned>(&self, path: &str, query: &(impl Serialize + ?Sized)) -> Result<T, TopggError> {
+        let url = format!("{}{}", self.base_url, path);
+        let res = check_response(self.send(self.client.get(&url).query(query))?)?;
+        let body = res.bytes()?;
+        serde_json::from_slice(&body).map_err(TopggError::Decode)
+    }
+
+
+    fn get_json<T: DeserializeOwned>(&self, url: &str) -> Option<T> {
+        let body = self.send(self.client.get(url)).ok()?.bytes().ok()?;
+        serde_json::from_slice(&body).ok()
+    }
+
+
+    /// Sends the request with the token once the rate limit allows it, pausing every request after a 429.
+    fn send(&self, req: reqwest::blocking::RequestBuilder) -> reqwest::Result<reqwest::blocking::Response> {
+        let req = req.header("Authorization", expose_token(&self.token)).build()?;
+        self.rate_limit.wait_blocking(req.url().path());
+        event!(tracing::Level::DEBUG, method = %req.method(), url = %req.url(), "sending request");
+        let res = self.client.execute(req)?;
+        if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = res
+                .headers()
+                .get("retry-after")
+                .and_then(|value| value.to_str().ok()?.parse::<u64>().ok())
+                .map(Duration::from_secs);
+            self.rate_limit.pause(retry_after);
+        }
+        Ok(res)
+    }
+}
+impl std::fmt::Debug for Topgg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The token is left out so logging the client doesn't leak it.
+        f.debug_struct("Topgg")
+            .field("bot_id", &self.bot_id)
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+
+fn check_response(res: reqwest::blocking::Response) -> Result<reqwest::blocking::Response, TopggError> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+
+    let body = res.text()?;
+    let message = match serde_json::from_str::<ApiError>(&body) {
+        Ok(e) => e.error,
+        Err(_) if body.is_empty() => status.canonical_reason().unwrap_or("unknown error").to_string(),
+        Err(_) => body,
+    };
+    Err(TopggError::Api {
+        status: status.as_u16(),
+        message,
+    })
+}
@@ -0,0 +1,68 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+
+
+/// Stops requests from being sent while top.gg keeps failing, see `Topgg::circuit_breaker`.
+pub(crate) struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<State>,
+}
+
+enum State {
+    /// Requests are sent as normal.
+    Closed { failures: u32 },
+    /// Requests fail straight away until the cooldown is over.
+    Open { until: Instant },
+    /// One request is being sent to see whether top.gg has recovered. If it hasn't finished by
+    /// `retry_at` it's assumed to have been dropped.
+    HalfOpen { retry_at: Instant },
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(threshold: u32, cooldown: Duration) -> CircuitBreaker {
+        CircuitBreaker {
+            threshold: threshold.max(1),
+            cooldown,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+
+    /// Whether a request may be sent now. Once the cooldown is over a single request is let through
+    /// to probe top.gg, and if that one is dropped before finishing another is let through after
+    /// another cooldown.
+    pub(crate) fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        match *state {
+            State::Closed { .. } => true,
+            State::Open { until } | State::HalfOpen { retry_at: until } if now < until => false,
+            State::Open { .. } | State::HalfOpen { .. } => {
+                *state = State::HalfOpen {
+                    retry_at: now + self.cooldown,
+                };
+                true
+            }
+        }
+    }
+
+
+    /// Records how a request that was let through went.
+    pub(crate) fn record(&self, success: bool) {
+        let mut state = self.state.lock().unwrap();
+        let open = State::Open {
+            until: Instant::now() + self.cooldown,
+        };
+        *state = match (&*state, success) {
+            (_, true) => State::Closed { failures: 0 },
+            (State::Closed { failures }, false) if failures + 1 < self.threshold => State::Closed { failures: failures + 1 },
+            (State::Open { until }, false) => State::Open { until: *until },
+            (State::Closed { .. }, false) | (State::HalfOpen { .. }, false) => {
+                event!(tracing::Level::WARN, cooldown_ms = self.cooldown.as_millis() as u64, "top.gg keeps failing, pausing requests");
+                open
+            }
+        };
+    }
+}
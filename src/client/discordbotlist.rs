@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use serde::Serialize;
+
+use super::{check_response, default_client, BotList, TopggError};
+use crate::Snowflake;
+
+
+
+const BASE_URL: &str = "https://discordbotlist.com/api/v1";
+
+
+/// Posts your bot's stats to discordbotlist.com, for use alongside `Topgg` in a `MultiPoster`.
+/// Needs the `discordbotlist` feature.
+/// ## Examples
+/// ```
+/// let poster = topgg::MultiPoster::new()
+///     .add(topgg::Topgg::new(bot_id, topgg_token))
+///     .add(topgg::DiscordBotList::new(bot_id, dbl_token));
+/// ```
+pub struct DiscordBotList {
+    bot_id: Snowflake,
+    token: String,
+    client: reqwest::Client,
+}
+impl DiscordBotList {
+    /// Returns a new poster using the token from the bot's admin page on discordbotlist.com.
+    pub fn new(bot_id: impl Into<Snowflake>, token: String) -> DiscordBotList {
+        DiscordBotList {
+            bot_id: bot_id.into(),
+            token,
+            client: default_client(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Stats {
+    guilds: u32,
+}
+
+#[async_trait]
+impl BotList for DiscordBotList {
+    fn name(&self) -> &str {
+        "discordbotlist.com"
+    }
+
+    /// discordbotlist.com only takes the server count, so the shard count is ignored.
+    async fn post_stats(&self, server_count: u32, _shard_count: Option<u32>) -> Result<(), TopggError> {
+        let url = format!("{}/bots/{}/stats", BASE_URL, self.bot_id);
+        let res = self.client
+            .post(&url)
+            .header("Authorization", &self.token)
+            .json(&Stats { guilds: server_count })
+            .send()
+            .await?;
+        check_response(res).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use serde::Serialize;
+
+use super::{check_response, default_client, BotList, TopggError};
+use crate::Snowflake;
+
+
+
+const BASE_URL: &str = "https://discords.com/bots/api";
+
+
+/// Posts your bot's stats to discords.com, for use alongside `Topgg` in a `MultiPoster`.
+/// Needs the `discords` feature.
+/// ## Examples
+/// ```
+/// let poster = topgg::MultiPoster::new()
+///     .add(topgg::Topgg::new(bot_id, topgg_token))
+///     .add(topgg::Discords::new(bot_id, discords_token));
+/// ```
+pub struct Discords {
+    bot_id: Snowflake,
+    token: String,
+    client: reqwest::Client,
+}
+impl Discords {
+    /// Returns a new poster using the API key from the bot's page on discords.com.
+    pub fn new(bot_id: impl Into<Snowflake>, token: String) -> Discords {
+        Discords {
+            bot_id: bot_id.into(),
+            token,
+            client: default_client(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Stats {
+    server_count: u32,
+}
+
+#[async_trait]
+impl BotList for Discords {
+    fn name(&self) -> &str {
+        "discords.com"
+    }
+
+    /// discords.com only takes the server count, so the shard count is ignored.
+    async fn post_stats(&self, server_count: u32, _shard_count: Option<u32>) -> Result<(), TopggError> {
+        let url = format!("{}/bot/{}", BASE_URL, self.bot_id);
+        let res = self.client
+            .post(&url)
+            .header("Authorization", &self.token)
+            .json(&Stats { server_count })
+            .send()
+            .await?;
+        check_response(res).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+
+
+
+/// Hooks run around every request the client sends, added with `Topgg::middleware`. Requests can be
+/// changed before they're sent, such as to sign them or add a correlation ID, and responses can be
+/// looked at or changed before the client reads them. Every hook does nothing unless implemented.
+/// ## Examples
+/// ```
+/// use async_trait::async_trait;
+///
+/// struct CorrelationId;
+///
+/// #[async_trait]
+/// impl topgg::Middleware for CorrelationId {
+///     async fn on_request(&self, req: &mut reqwest::Request) {
+///         req.headers_mut().insert("x-correlation-id", new_correlation_id().parse().unwrap());
+///     }
+///
+///     async fn on_response(&self, req: &topgg::RequestSummary, res: &mut reqwest::Response) {
+///         audit_log(req, res.status()).await;
+///     }
+/// }
+///
+/// let client = topgg::Topgg::new(bot_id, token).middleware(CorrelationId);
+/// ```
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Called with each request just before it's sent, after the token has been added.
+    async fn on_request(&self, _req: &mut reqwest::Request) {}
+
+    /// Called with each response before the client reads it.
+    async fn on_response(&self, _req: &RequestSummary, _res: &mut reqwest::Response) {}
+
+    /// Called when a request couldn't be sent or its response couldn't be received.
+    async fn on_error(&self, _req: &RequestSummary, _error: &reqwest::Error) {}
+}
+
+
+/// What a response was for, since the request itself has been sent by the time it arrives.
+#[derive(Debug, Clone)]
+pub struct RequestSummary {
+    /// The HTTP method, such as `GET`.
+    pub method: reqwest::Method,
+    /// The full URL the request was sent to.
+    pub url: reqwest::Url,
+}
@@ -0,0 +1,66 @@
+use std::borrow::Cow;
+use opentelemetry::propagation::Injector;
+use opentelemetry::trace::{SpanKind, Status, TraceContextExt, Tracer};
+use opentelemetry::{global, Context, KeyValue};
+use reqwest::header::{HeaderName, HeaderValue};
+
+
+
+/// An OpenTelemetry span covering one request to top.gg.
+pub(crate) struct RequestSpan {
+    cx: Context,
+}
+
+
+/// Starts a client span for the request as a child of the current context, and adds the headers
+/// for the configured propagator so top.gg's side of the trace could be joined up.
+pub(crate) fn start(req: &mut reqwest::Request) -> RequestSpan {
+    let parent = Context::current();
+    let tracer = global::tracer("topgg");
+    let mut attributes = vec![
+        KeyValue::new("http.request.method", req.method().as_str().to_string()),
+        KeyValue::new("url.full", req.url().to_string()),
+    ];
+    if let Some(host) = req.url().host_str() {
+        attributes.push(KeyValue::new("server.address", host.to_string()));
+    }
+    let span = tracer
+        .span_builder(format!("{} {}", req.method(), req.url().path()))
+        .with_kind(SpanKind::Client)
+        .with_attributes(attributes)
+        .start_with_context(&tracer, &parent);
+    let cx = parent.with_span(span);
+
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&cx, &mut HeaderInjector(req.headers_mut()))
+    });
+    RequestSpan { cx }
+}
+
+
+impl RequestSpan {
+    /// Ends the span with the status of the response, or the error if there wasn't one.
+    pub(crate) fn end(self, result: Result<u16, String>) {
+        let span = self.cx.span();
+        match result {
+            Ok(status) => {
+                span.set_attribute(KeyValue::new("http.response.status_code", status as i64));
+                if status >= 400 {
+                    span.set_status(Status::error(Cow::Owned(format!("top.gg responded with {}", status))));
+                }
+            }
+            Err(e) => span.set_status(Status::error(Cow::Owned(e))),
+        }
+        span.end();
+    }
+}
+
+
+struct HeaderInjector<'a>(&'a mut reqwest::header::HeaderMap);
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(&value)) {
+            self.0.insert(name, value);
+        }
+    }
+}
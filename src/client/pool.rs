@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{default_client, secure_token, token_bot_id, ConfigError, RateLimit, Token, Topgg};
+use crate::Snowflake;
+
+
+
+/// Clients for several bots run from the same process. They send their requests through one
+/// connection pool and share one rate limit, so together they stay within what top.gg allows.
+/// ## Examples
+/// ```
+/// let pool = topgg::TopggPool::new()
+///     .add(music_bot_id, music_token)
+///     .add(moderation_bot_id, moderation_token);
+///
+/// if let Some(client) = pool.for_bot(music_bot_id) {
+///     client.post_bot_stats(topgg::StatsUpdate::server_count(server_count)).await?;
+/// }
+/// ```
+pub struct TopggPool {
+    client: reqwest::Client,
+    rate_limit: Arc<RateLimit>,
+    bots: HashMap<Snowflake, Topgg>,
+}
+impl TopggPool {
+    /// Returns a new pool with no bots in it.
+    pub fn new() -> TopggPool {
+        TopggPool {
+            client: default_client(),
+            rate_limit: Arc::new(RateLimit::new()),
+            bots: HashMap::new(),
+        }
+    }
+
+
+    /// Adds a bot and its token, replacing the client for the bot if it was already added.
+    pub fn add(mut self, bot_id: impl Into<Snowflake>, token: String) -> TopggPool {
+        self.insert(bot_id.into(), secure_token(token));
+        self
+    }
+
+
+    /// Adds a bot with a token kept in a `SecretString`. Needs the `secrecy` feature.
+    #[cfg(feature = "secrecy")]
+    pub fn add_secret(mut self, bot_id: impl Into<Snowflake>, token: secrecy::SecretString) -> TopggPool {
+        self.insert(bot_id.into(), token);
+        self
+    }
+
+
+    /// Adds the bot the token belongs to, reading its ID from the token like `Topgg::from_token`.
+    pub fn add_token(mut self, token: String) -> Result<TopggPool, ConfigError> {
+        let bot_id = token_bot_id(&token)?;
+        self.insert(bot_id, secure_token(token));
+        Ok(self)
+    }
+
+
+    /// The client for the bot, if it was added.
+    pub fn for_bot(&self, bot_id: impl Into<Snowflake>) -> Option<&Topgg> {
+        self.bots.get(&bot_id.into())
+    }
+
+
+    /// The IDs of every bot in the pool.
+    pub fn bot_ids(&self) -> impl Iterator<Item = Snowflake> + '_ {
+        self.bots.keys().copied()
+    }
+
+
+    fn insert(&mut self, bot_id: Snowflake, token: Token) {
+        let client = Topgg::with_shared(bot_id, token, self.client.clone(), self.rate_limit.clone());
+        self.bots.insert(bot_id, client);
+    }
+}
+impl Default for TopggPool {
+    fn default() -> TopggPool {
+        TopggPool::new()
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "metrics")]
+use super::telemetry;
+#[cfg(feature = "redis-ratelimit")]
+mod redis;
+#[cfg(feature = "redis-ratelimit")]
+pub use self::redis::RedisRateLimit;
+
+
+
+/// How long to pause for after a 429 that didn't say how long to wait.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// How long a request which could be sent waits before trying again, when it stood aside for a more
+/// important one that can be sent too.
+const YIELD_DELAY: Duration = Duration::from_millis(5);
+
+/// top.gg's limit on every request, whichever route it's to.
+const GLOBAL_QUOTA: Quota = Quota {
+    requests: 100,
+    per: Duration::from_secs(1),
+};
+
+
+/// Keeps a client, or every client in a `TopggPool`, within top.gg's rate limits: at most 100 requests a
+/// second in total, at most 60 a minute to the `/bots` routes together unless set otherwise, and none at
+/// all for a while after top.gg responds with a 429.
+///
+/// Each family of routes, like `/bots/{id}/check`, is also kept to the `/bots` limit in its own bucket,
+/// so one family can't take the whole shared limit and a burst of vote checks doesn't hold up posting
+/// stats. Requests waiting on the limits they share go in order of `Priority` rather
+/// than whichever wakes up first, so stats still get posted when the client is near the global limit
+/// or coming out of a pause.
+pub(crate) struct RateLimit {
+    /// The limit for the `/bots` routes together and for each family of them, or `None` to not limit
+    /// requests locally.
+    quota: Option<Quota>,
+    #[cfg(feature = "redis-ratelimit")]
+    shared: Option<RedisRateLimit>,
+    state: Mutex<State>,
+}
+
+#[derive(Clone, Copy)]
+struct Quota {
+    requests: u32,
+    per: Duration,
+}
+
+struct State {
+    global: Bucket,
+    /// Every request to a `/bots` route, whichever family it's in.
+    bots: Bucket,
+    routes: HashMap<String, Bucket>,
+    paused_until: Option<Instant>,
+    /// The requests which are waiting, in the order they should be sent, with the route they're to.
+    waiting: BTreeMap<(Priority, u64), Option<String>>,
+    next_waiter: u64,
+    waits: u64,
+    waited: Duration,
+}
+
+/// How important a request is, most important first.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Priority {
+    /// Posting or getting stats, which keeps the bot's listing up to date.
+    Stats,
+    /// Checking votes, often for a user waiting on a command.
+    Votes,
+    /// Everything else, like looking up bots and users or listing voters.
+    Lookup,
+}
+impl Priority {
+    fn of(route: Option<&str>) -> Priority {
+        match route {
+            Some(route) if route.ends_with("/stats") => Priority::Stats,
+            Some(route) if route.ends_with("/check") => Priority::Votes,
+            _ => Priority::Lookup,
+        }
+    }
+}
+
+/// A place in the queue of waiting requests, given up when the request is sent or stops waiting.
+struct Waiter<'a> {
+    rate_limit: &'a RateLimit,
+    key: (Priority, u64),
+}
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        self.rate_limit.state.lock().unwrap().waiting.remove(&self.key);
+    }
+}
+
+impl RateLimit {
+    pub(crate) fn new() -> RateLimit {
+        RateLimit::with_quota(Some((60, Duration::from_secs(60))))
+    }
+
+
+    /// Allows `requests` requests in every `per` to the `/bots` routes together and to each family of
+    /// them, or any number of requests at all if `quota` is `None`. Requests still wait out the pause after a 429 either way.
+    pub(crate) fn with_quota(quota: Option<(u32, Duration)>) -> RateLimit {
+        RateLimit {
+            quota: quota.map(|(requests, per)| Quota { requests, per }),
+            #[cfg(feature = "redis-ratelimit")]
+            shared: None,
+            state: Mutex::new(State {
+                global: Bucket::default(),
+                bots: Bucket::default(),
+                routes: HashMap::new(),
+                paused_until: None,
+                waiting: BTreeMap::new(),
+                next_waiter: 0,
+                waits: 0,
+                waited: Duration::from_secs(0),
+            }),
+        }
+    }
+
+
+    /// Also keeps to a limit kept in Redis, shared with other processes.
+    #[cfg(feature = "redis-ratelimit")]
+    pub(crate) fn shared(mut self, shared: Option<RedisRateLimit>) -> RateLimit {
+        self.shared = shared;
+        self
+    }
+
+
+    /// Waits until a request to `path` can be sent, and counts it as sent.
+    pub(crate) async fn wait(&self, path: &str) {
+        let route = route(path);
+        let started = Instant::now();
+        let waited = self.wait_local(route.as_deref()).await;
+        #[cfg(feature = "redis-ratelimit")]
+        let waited = self.wait_shared(route.as_deref()).await || waited;
+        if waited {
+            self.record_wait(started);
+        }
+    }
+
+
+    /// Blocks the thread until a request to `path` can be sent, and counts it as sent. The limit shared
+    /// through Redis isn't kept to, since it needs a runtime.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn wait_blocking(&self, path: &str) {
+        let route = route(path);
+        let priority = Priority::of(route.as_deref());
+        let started = Instant::now();
+        let mut waiter = None;
+        while let Some(ready_at) = self.take(route.as_deref(), priority, waiter.as_ref()) {
+            if waiter.is_none() {
+                waiter = Some(self.enqueue(route.as_deref(), priority));
+            }
+            std::thread::sleep(ready_at.saturating_duration_since(Instant::now()));
+        }
+        if waiter.is_some() {
+            self.record_wait(started);
+        }
+    }
+
+
+    /// Waits until this process's limits allow a request to `route`, and counts it as sent. Returns
+    /// whether it had to wait.
+    async fn wait_local(&self, route: Option<&str>) -> bool {
+        let priority = Priority::of(route);
+        let mut waiter = None;
+        while let Some(ready_at) = self.take(route, priority, waiter.as_ref()) {
+            if waiter.is_none() {
+                event!(tracing::Level::DEBUG, route = ?route, "waiting for the rate limit");
+                waiter = Some(self.enqueue(route, priority));
+            }
+            crate::rt::sleep_until(ready_at).await;
+        }
+        waiter.is_some()
+    }
+
+
+    /// Counts a request to `route` as sent if this process's limits allow it now and no more important
+    /// request that's waiting could be sent instead, and otherwise returns when to try again.
+    fn take(&self, route: Option<&str>, priority: Priority, waiter: Option<&Waiter<'_>>) -> Option<Instant> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        if let Some(ready_at) = self.ready_at(&mut state, route, now) {
+            return Some(ready_at);
+        }
+
+        // Whatever is holding up the requests ahead, it isn't the global limit or a pause, since those
+        // would hold this one up too. If one of them is only waiting to wake up, it goes first.
+        let key = waiter.map_or((priority, u64::MAX), |waiter| waiter.key);
+        let ahead = state
+            .waiting
+            .range(..key)
+            .map(|(_, route)| route.clone())
+            .collect::<Vec<_>>();
+        if ahead.iter().any(|route| self.route_ready(&mut state, route.as_deref(), now)) {
+            return Some(now + YIELD_DELAY);
+        }
+
+        if self.quota.is_some() {
+            state.global.sent.push_back(now);
+            if let Some(route) = route {
+                state.bots.sent.push_back(now);
+                state.routes.entry(route.to_string()).or_default().sent.push_back(now);
+            }
+        }
+        state.waiting.remove(&key);
+        None
+    }
+
+
+    /// Puts a request to `route` in the queue of waiting requests, behind any as important.
+    fn enqueue(&self, route: Option<&str>, priority: Priority) -> Waiter<'_> {
+        let mut state = self.state.lock().unwrap();
+        let key = (priority, state.next_waiter);
+        state.next_waiter += 1;
+        state.waiting.insert(key, route.map(str::to_string));
+        Waiter { rate_limit: self, key }
+    }
+
+
+    /// Whether the `/bots` bucket and the bucket for `route` have room for another request now.
+    fn route_ready(&self, state: &mut State, route: Option<&str>, now: Instant) -> bool {
+        let (quota, route) = match (self.quota, route) {
+            (Some(quota), Some(route)) => (quota, route),
+            _ => return true,
+        };
+        if state.bots.ready_at(quota, now).is_some() {
+            return false;
+        }
+        match state.routes.get_mut(route) {
+            Some(bucket) => bucket.ready_at(quota, now).is_none(),
+            None => true,
+        }
+    }
+
+
+    fn record_wait(&self, started: Instant) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.waits += 1;
+            state.waited += started.elapsed();
+        }
+        event!(tracing::Level::DEBUG, waited_ms = started.elapsed().as_millis() as u64, "rate limit wait over");
+        #[cfg(feature = "metrics")]
+        telemetry::record_wait(started.elapsed());
+    }
+
+
+    /// Waits until the limit shared through Redis allows a request to `route`, if there is one. Returns
+    /// whether it had to wait.
+    #[cfg(feature = "redis-ratelimit")]
+    async fn wait_shared(&self, route: Option<&str>) -> bool {
+        let (shared, quota) = match (&self.shared, self.quota) {
+            (Some(shared), Some(quota)) => (shared, quota),
+            _ => return false,
+        };
+        let mut buckets = vec![("global", GLOBAL_QUOTA)];
+        if let Some(route) = route {
+            buckets.push(("bots/*", quota));
+            buckets.push((route, quota));
+        }
+        shared.wait(&buckets).await
+    }
+
+
+    /// Stops every request from being sent for `retry_after`, or a minute if top.gg didn't say how
+    /// long, since its rate limits apply across all endpoints.
+    pub(crate) fn pause(&self, retry_after: Option<Duration>) {
+        let until = Instant::now() + retry_after.unwrap_or(DEFAULT_RETRY_AFTER);
+        let mut state = self.state.lock().unwrap();
+        if state.paused_until < Some(until) {
+            state.paused_until = Some(until);
+        }
+    }
+
+
+    /// How many more requests can be sent to any route right now without waiting, going by the
+    /// busiest bucket. This is `u32::MAX` if requests aren't limited.
+    pub(crate) fn remaining(&self) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        if paused_until(&mut state, now).is_some() {
+            return 0;
+        }
+        let quota = match self.quota {
+            Some(quota) => quota,
+            None => return u32::MAX,
+        };
+        let State { global, bots, routes, .. } = &mut *state;
+        routes
+            .values_mut()
+            .map(|bucket| bucket.remaining(quota, now))
+            .fold(global.remaining(GLOBAL_QUOTA, now).min(bots.remaining(quota, now)), u32::min)
+    }
+
+
+    /// How long until a request to any route could be sent, which is zero if one can be sent now.
+    pub(crate) fn time_until_ready(&self) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let paused_until = paused_until(&mut state, now);
+        let State { global, bots, routes, .. } = &mut *state;
+        let ready_at = self.quota.and_then(|quota| {
+            routes
+                .values_mut()
+                .map(|bucket| bucket.ready_at(quota, now))
+                .fold(global.ready_at(GLOBAL_QUOTA, now).max(bots.ready_at(quota, now)), Option::max)
+        });
+        paused_until.max(ready_at).map_or(Duration::from_secs(0), |ready_at| ready_at - now)
+    }
+
+
+    pub(crate) fn stats(&self) -> RateLimitStats {
+        let state = self.state.lock().unwrap();
+        RateLimitStats {
+            limit: self.quota.map(|quota| quota.requests),
+            waits: state.waits,
+            waited: state.waited,
+        }
+    }
+
+
+    /// When the next request to `route` can be sent, or `None` if it can be sent now.
+    fn ready_at(&self, state: &mut State, route: Option<&str>, now: Instant) -> Option<Instant> {
+        if let Some(paused_until) = paused_until(state, now) {
+            return Some(paused_until);
+        }
+        let quota = self.quota?;
+        let global = state.global.ready_at(GLOBAL_QUOTA, now);
+        let route = route.and_then(|route| {
+            let bots = state.bots.ready_at(quota, now);
+            let family = state.routes.get_mut(route).and_then(|bucket| bucket.ready_at(quota, now));
+            bots.max(family)
+        });
+        global.max(route)
+    }
+}
+
+
+/// When requests are paused until after a 429, forgetting the pause once it's over.
+fn paused_until(state: &mut State, now: Instant) -> Option<Instant> {
+    if matches!(state.paused_until, Some(paused_until) if paused_until <= now) {
+        state.paused_until = None;
+    }
+    state.paused_until
+}
+
+
+/// The family of `/bots` routes `path` belongs to, with the IDs taken out, like `bots/{id}/check`. Other
+/// routes only count towards the global limit.
+fn route(path: &str) -> Option<String> {
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| if segment.bytes().all(|b| b.is_ascii_digit()) { "{id}" } else { segment })
+        .collect::<Vec<_>>();
+    if !segments.contains(&"bots") {
+        return None;
+    }
+    Some(segments.join("/"))
+}
+
+
+/// The requests sent within the last window of a quota, oldest first.
+#[derive(Default)]
+struct Bucket {
+    sent: VecDeque<Instant>,
+}
+impl Bucket {
+    /// When the next request can be sent under `quota`, or `None` if it can be sent now. Forgets the
+    /// requests that have left the window.
+    fn ready_at(&mut self, quota: Quota, now: Instant) -> Option<Instant> {
+        while matches!(self.sent.front(), Some(sent) if *sent + quota.per <= now) {
+            self.sent.pop_front();
+        }
+        if (self.sent.len() as u32) < quota.requests {
+            return None;
+        }
+        self.sent.front().map(|sent| *sent + quota.per)
+    }
+
+
+    fn remaining(&mut self, quota: Quota, now: Instant) -> u32 {
+        self.ready_at(quota, now);
+        quota.requests.saturating_sub(self.sent.len() as u32)
+    }
+}
+
+
+/// How much a client has been held back by its rate limit, from `Topgg::rate_limit_stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStats {
+    /// How many requests can be sent to each family of `/bots` routes in a window, or `None` if they
+    /// aren't limited locally.
+    pub limit: Option<u32>,
+    /// How many requests had to wait before being sent.
+    pub waits: u64,
+    /// How long those requests waited in total.
+    pub waited: Duration,
+}
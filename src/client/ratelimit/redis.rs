@@ -0,0 +1,114 @@
+use std::time::Duration;
+use ::redis::aio::ConnectionManager;
+use ::redis::Script;
+
+use super::Quota;
+
+
+
+/// Takes a token from every bucket in `KEYS`, or none at all if any of them is empty. `ARGV` holds each
+/// bucket's capacity and how many milliseconds it takes to refill. Returns 0 if the tokens were taken,
+/// or how many milliseconds to wait before trying again. Redis's clock is used so every process agrees
+/// on the time.
+const TAKE: &str = r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local tokens = {}
+local wait = 0
+for i, key in ipairs(KEYS) do
+    local capacity = tonumber(ARGV[i * 2 - 1])
+    local per = tonumber(ARGV[i * 2])
+    local bucket = redis.call('HMGET', key, 'tokens', 'at')
+    local left = tonumber(bucket[1]) or capacity
+    local at = tonumber(bucket[2]) or now
+    left = math.min(capacity, left + (now - at) * capacity / per)
+    if left < 1 then
+        wait = math.max(wait, math.ceil((1 - left) * per / capacity))
+    end
+    tokens[i] = left
+end
+if wait > 0 then
+    return wait
+end
+for i, key in ipairs(KEYS) do
+    redis.call('HSET', key, 'tokens', tokens[i] - 1, 'at', now)
+    redis.call('PEXPIRE', key, tonumber(ARGV[i * 2]))
+end
+return 0
+"#;
+
+
+/// A rate limit kept in Redis, for when several processes share one token and together have to stay
+/// within top.gg's limits. Each bucket the client limits itself with also gets a token bucket in Redis,
+/// which every process takes from before sending a request. Set it with `TopggBuilder::redis_rate_limit`.
+///
+/// If Redis can't be reached requests fall back to the process's own limit rather than failing. Needs
+/// the `redis-ratelimit` feature.
+/// ## Examples
+/// ```
+/// let limiter = topgg::RedisRateLimit::connect("redis://127.0.0.1/").await?;
+/// let client = topgg::Topgg::builder(bot_id, token)
+///     .redis_rate_limit(limiter)
+///     .build()?;
+/// ```
+#[derive(Clone)]
+pub struct RedisRateLimit {
+    conn: ConnectionManager,
+    prefix: String,
+    script: Script,
+}
+impl RedisRateLimit {
+    /// Connects to the Redis server at `url`, reconnecting by itself if the connection drops.
+    pub async fn connect(url: &str) -> Result<RedisRateLimit, ::redis::RedisError> {
+        let client = ::redis::Client::open(url)?;
+        let conn = ConnectionManager::new(client).await?;
+        Ok(RedisRateLimit::new(conn))
+    }
+
+
+    /// Returns a rate limit using an existing connection.
+    pub fn new(conn: ConnectionManager) -> RedisRateLimit {
+        RedisRateLimit {
+            conn,
+            prefix: "topgg:ratelimit".to_string(),
+            script: Script::new(TAKE),
+        }
+    }
+
+
+    /// Sets what the keys start with, `topgg:ratelimit` by default. Processes using the same token have
+    /// to use the same prefix, and ones using different tokens different prefixes.
+    pub fn prefix(mut self, prefix: &str) -> RedisRateLimit {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+
+    /// Waits until a token could be taken from every one of `buckets`, and takes them. Returns whether
+    /// it had to wait.
+    pub(super) async fn wait(&self, buckets: &[(&str, Quota)]) -> bool {
+        let mut waited = false;
+        loop {
+            let mut invocation = self.script.prepare_invoke();
+            for (bucket, quota) in buckets {
+                // Braces would be taken as a hash tag by Redis Cluster.
+                let bucket = bucket.replace('{', "").replace('}', "");
+                invocation
+                    .key(format!("{}:{}", self.prefix, bucket))
+                    .arg(quota.requests)
+                    .arg(quota.per.as_millis() as u64);
+            }
+            match invocation.invoke_async::<_, u64>(&mut self.conn.clone()).await {
+                Ok(0) => return waited,
+                Ok(wait_ms) => {
+                    waited = true;
+                    crate::rt::sleep(Duration::from_millis(wait_ms)).await;
+                }
+                Err(e) => {
+                    event!(tracing::Level::WARN, error = %e, "couldn't reach the shared rate limit in redis, using the local one");
+                    return waited;
+                }
+            }
+        }
+    }
+}
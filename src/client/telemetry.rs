@@ -0,0 +1,45 @@
+use std::time::Duration;
+use reqwest::Url;
+
+
+
+/// Records how long a request waited for the client's own rate limiter.
+pub(crate) fn record_wait(waited: Duration) {
+    ::metrics::histogram!("topgg_client_rate_limit_wait_seconds").record(waited.as_secs_f64());
+}
+
+
+/// Records a request that was sent, with its status or `None` if no response was received.
+pub(crate) fn record_response(url: &Url, status: Option<u16>, latency: Duration) {
+    let endpoint = endpoint(url);
+    ::metrics::counter!("topgg_client_requests_total", "endpoint" => endpoint.clone()).increment(1);
+    ::metrics::histogram!("topgg_client_request_duration_seconds", "endpoint" => endpoint.clone())
+        .record(latency.as_secs_f64());
+
+    let kind = match status {
+        None => "http",
+        Some(429) => "rate_limited",
+        Some(status) if status >= 500 => "server",
+        Some(status) if status >= 400 => "client",
+        Some(_) => return,
+    };
+    ::metrics::counter!("topgg_client_errors_total", "endpoint" => endpoint, "kind" => kind).increment(1);
+}
+
+
+/// The route of the URL with IDs replaced by `:id`, like `/bots/:id/stats`, so there's one series per
+/// route rather than one per bot or user.
+fn endpoint(url: &Url) -> String {
+    let path = url.path();
+    let path = path.strip_prefix("/api").unwrap_or(path);
+    path.split('/')
+        .map(|segment| {
+            if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+                ":id"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use super::local_response;
+
+
+
+/// Whether a `Cassette` records responses from top.gg or replays the recorded ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcrMode {
+    /// Sends every request to top.gg and saves its response, replacing any recorded before.
+    Record,
+    /// Only answers with recorded responses. Requests that weren't recorded get a 500 response
+    /// saying so, and nothing is sent to top.gg.
+    Replay,
+    /// Replays the responses that were recorded and records the rest.
+    Auto,
+}
+
+
+/// A directory of recorded responses from top.gg, so tests can go through the whole client without
+/// a token once the responses have been recorded. Each request is saved to its own JSON file, named
+/// after its method and path, which can be checked in alongside the tests. The token isn't saved.
+/// Needs the `vcr` feature.
+/// ## Examples
+/// ```
+/// use topgg::{Cassette, VcrMode};
+///
+/// // Records on the first run and replays afterwards.
+/// let client = topgg::Topgg::new(bot_id, token)
+///     .vcr(Cassette::new("tests/fixtures/topgg", VcrMode::Auto));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cassette {
+    dir: PathBuf,
+    mode: VcrMode,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Recording {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+impl Cassette {
+    /// A cassette kept in `dir`, which is created when the first response is recorded.
+    pub fn new(dir: impl AsRef<Path>, mode: VcrMode) -> Cassette {
+        Cassette {
+            dir: dir.as_ref().to_path_buf(),
+            mode,
+        }
+    }
+
+
+    /// Answers the request from the recordings, or sends it and records the response, depending on the mode.
+    pub(crate) async fn execute(&self, client: &reqwest::Client, req: reqwest::Request) -> reqwest::Result<reqwest::Response> {
+        let path = self.path(&req);
+        let recorded = path.exists();
+        match self.mode {
+            VcrMode::Replay | VcrMode::Auto if recorded => return Ok(load(&path)),
+            VcrMode::Replay => {
+                let message = format!("no recorded response for {} {} in {}", req.method(), req.url().path(), self.dir.display());
+                return Ok(local_response(500, &message));
+            }
+            VcrMode::Record | VcrMode::Auto => {}
+        }
+
+        let res = client.execute(req).await?;
+        let status = res.status().as_u16();
+        let headers: BTreeMap<String, String> = res
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let body = res.bytes().await?;
+        let recording = Recording {
+            status,
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        };
+        if let Err(e) = save(&path, &recording) {
+            event!(tracing::Level::WARN, error = %e, path = %path.display(), "failed to record response");
+        }
+        Ok(response(recording))
+    }
+
+
+    /// The file the response to the request is recorded in. Requests to the same path with different
+    /// queries or bodies are told apart by a hash of them.
+    fn path(&self, req: &reqwest::Request) -> PathBuf {
+        let url = req.url();
+        let name: String = url
+            .path()
+            .trim_matches('/')
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let mut hash = Fnv::new();
+        hash.write(url.query().unwrap_or("").as_bytes());
+        if let Some(body) = req.body().and_then(|body| body.as_bytes()) {
+            hash.write(body);
+        }
+        self.dir.join(format!("{}_{}_{:016x}.json", req.method(), name, hash.finish()))
+    }
+}
+
+
+fn load(path: &Path) -> reqwest::Response {
+    let recording = fs::read(path)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| serde_json::from_slice::<Recording>(&bytes).map_err(|e| e.to_string()));
+    match recording {
+        Ok(recording) => response(recording),
+        Err(e) => local_response(500, &format!("couldn't read the recording {}: {}", path.display(), e)),
+    }
+}
+
+
+fn save(path: &Path, recording: &Recording) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(recording)?)
+}
+
+
+fn response(recording: Recording) -> reqwest::Response {
+    let mut res = http::Response::new(recording.body.into_bytes());
+    *res.status_mut() = http::StatusCode::from_u16(recording.status).unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+    for (name, value) in recording.headers {
+        if let (Ok(name), Ok(value)) = (http::HeaderName::from_bytes(name.as_bytes()), http::HeaderValue::from_str(&value)) {
+            res.headers_mut().insert(name, value);
+        }
+    }
+    reqwest::Response::from(res)
+}
+
+
+/// FNV-1a, used for the file names since it gives the same hash on every platform and Rust version.
+struct Fnv(u64);
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
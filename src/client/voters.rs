@@ -0,0 +1,113 @@
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+use futures::stream::{self, Stream};
+
+use super::Topgg;
+use crate::Snowflake;
+
+
+
+/// Remembers who was in the last votes fetched from top.gg, to tell who voted since. This answers "who
+/// voted since I last checked" for bots without a webhook.
+///
+/// top.gg only lists the last 1000 votes, and each user once per vote, so someone who votes again while
+/// their last vote is still listed isn't new.
+/// ## Examples
+/// ```
+/// let mut voters = topgg::VoterDiff::new();
+/// loop {
+///     for user_id in voters.poll(&client).await.unwrap_or_default() {
+///         thank(user_id).await;
+///     }
+///     tokio::time::sleep(Duration::from_secs(60)).await;
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VoterDiff {
+    seen: HashSet<Snowflake>,
+}
+impl VoterDiff {
+    /// Returns a diff which hasn't seen any votes, so every voter in the first poll is new.
+    pub fn new() -> VoterDiff {
+        VoterDiff::default()
+    }
+
+
+    /// Returns a diff which has already seen `voters`, such as the ones saved by a previous run.
+    pub fn with_seen(voters: impl IntoIterator<Item = impl Into<Snowflake>>) -> VoterDiff {
+        VoterDiff {
+            seen: voters.into_iter().map(Into::into).collect(),
+        }
+    }
+
+
+    /// Fetches the votes for the client's bot and returns the users who weren't in the last ones, or
+    /// `None` if they couldn't be fetched.
+    pub async fn poll(&mut self, client: &Topgg) -> Option<Vec<Snowflake>> {
+        let votes = client.my_votes().await?;
+        Some(self.diff(votes))
+    }
+
+
+    /// Returns the users in `votes` who weren't in the last votes, in the order top.gg listed them, and
+    /// remembers `votes` as the last ones.
+    pub fn diff(&mut self, votes: Vec<Snowflake>) -> Vec<Snowflake> {
+        let new = new_voters(&votes, &self.seen);
+        self.seen = votes.into_iter().collect();
+        new
+    }
+
+
+    /// The users in the last votes.
+    pub fn seen(&self) -> &HashSet<Snowflake> {
+        &self.seen
+    }
+}
+
+
+/// See `Topgg::poll_votes`.
+pub(crate) fn poll(client: &Topgg, interval: Duration) -> impl Stream<Item = Snowflake> + '_ {
+    struct Poll {
+        diff: VoterDiff,
+        started: bool,
+        seeded: bool,
+        pending: VecDeque<Snowflake>,
+    }
+
+    let start = Poll {
+        diff: VoterDiff::new(),
+        started: false,
+        seeded: false,
+        pending: VecDeque::new(),
+    };
+    stream::unfold(start, move |mut poll| async move {
+        loop {
+            if let Some(user_id) = poll.pending.pop_front() {
+                return Some((user_id, poll));
+            }
+            if poll.started {
+                crate::rt::sleep(interval).await;
+            }
+            poll.started = true;
+            match poll.diff.poll(client).await {
+                Some(new) if poll.seeded => poll.pending.extend(new),
+                // The votes listed when polling starts were already made.
+                Some(_) => poll.seeded = true,
+                None => {
+                    event!(tracing::Level::WARN, "polling the votes failed, trying again next interval");
+                }
+            }
+        }
+    })
+}
+
+
+/// The users in `votes` who aren't in `previous`, each once.
+pub(crate) fn new_voters(votes: &[Snowflake], previous: &HashSet<Snowflake>) -> Vec<Snowflake> {
+    let mut added = HashSet::new();
+    votes
+        .iter()
+        .copied()
+        .filter(|user_id| !previous.contains(user_id) && added.insert(*user_id))
+        .collect()
+}
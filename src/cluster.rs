@@ -0,0 +1,264 @@
+//! Posting the stats of a bot whose shards are spread over several processes. Each process reports the
+//! server counts of its own shards to a `ClusterBackend` shared by all of them, and one process at a time
+//! is elected to post the combined counts, so top.gg never gets conflicting partial stats. Needs the
+//! `client` feature.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+
+use crate::{StatsUpdate, TopggApi, TopggError};
+
+#[cfg(feature = "redis-cluster")]
+mod redis;
+#[cfg(feature = "redis-cluster")]
+pub use self::redis::RedisCluster;
+
+
+
+/// Collects the server counts of the shards run by this process and, while it's the elected poster,
+/// posts the counts of every shard in the cluster.
+///
+/// The shards are only posted once every one of them from 0 to `shard_count` has been reported, so a
+/// process that hasn't started yet doesn't make the bot look smaller than it is.
+/// ## Examples
+/// ```
+/// use topgg::cluster::{RedisCluster, StatsCoordinator};
+///
+/// let backend = RedisCluster::connect("redis://127.0.0.1/").await?;
+/// let coordinator = StatsCoordinator::new(client, backend, shard_count);
+///
+/// // whenever a shard's guilds change
+/// coordinator.report(shard_id, guild_count).await?;
+///
+/// // in the background
+/// coordinator.run(Duration::from_secs(5 * 60)).await;
+/// ```
+pub struct StatsCoordinator<B = MemoryCluster> {
+    client: Box<dyn TopggApi>,
+    backend: B,
+    process_id: String,
+    shard_count: u32,
+    ttl: Duration,
+    shards: Mutex<BTreeMap<u32, u32>>,
+}
+impl<B: ClusterBackend> StatsCoordinator<B> {
+    /// Returns a coordinator posting with `client` for a bot with `shard_count` shards in total.
+    pub fn new(client: impl TopggApi + 'static, backend: B, shard_count: u32) -> StatsCoordinator<B> {
+        let started = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        StatsCoordinator {
+            client: Box::new(client),
+            backend,
+            process_id: format!("{}-{}", std::process::id(), started.as_nanos()),
+            shard_count,
+            ttl: Duration::from_secs(10 * 60),
+            shards: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+
+    /// Sets the name this process goes by in the cluster, which has to be different for every process.
+    /// By default it's made from the process ID and the time the coordinator was made.
+    pub fn process_id(mut self, process_id: impl Into<String>) -> StatsCoordinator<B> {
+        self.process_id = process_id.into();
+        self
+    }
+
+
+    /// Sets how long the shard counts reported by this process, and its term as the poster, last
+    /// without being renewed. It's 10 minutes by default and has to be longer than the interval given
+    /// to `run`, otherwise the shards of a running process will be forgotten between renewals.
+    pub fn ttl(mut self, ttl: Duration) -> StatsCoordinator<B> {
+        self.ttl = ttl;
+        self
+    }
+
+
+    /// Saves the number of servers one of this process's shards is in, and shares the counts of all of
+    /// them with the cluster. Fails with `ClusterError::InvalidShard` if `shard_id` isn't less than the
+    /// shard count.
+    pub async fn report(&self, shard_id: u32, server_count: u32) -> Result<(), ClusterError> {
+        if shard_id >= self.shard_count {
+            return Err(ClusterError::InvalidShard {
+                shard_id,
+                shard_count: self.shard_count,
+            });
+        }
+        self.shards.lock().unwrap().insert(shard_id, server_count);
+        self.publish().await
+    }
+
+
+    /// Forgets one of this process's shards, such as when it's moved to another process.
+    pub async fn remove(&self, shard_id: u32) -> Result<(), ClusterError> {
+        self.shards.lock().unwrap().remove(&shard_id);
+        self.publish().await
+    }
+
+
+    /// Whether this process is the one which posts stats, electing it if no process is.
+    pub async fn is_poster(&self) -> Result<bool, ClusterError> {
+        self.backend.elect(&self.process_id, self.ttl).await
+    }
+
+
+    /// Posts the server counts of every shard in the cluster if this process is the elected poster and
+    /// all the shards have been reported. Returns whether anything was posted.
+    pub async fn post(&self) -> Result<bool, ClusterError> {
+        if !self.is_poster().await? {
+            return Ok(false);
+        }
+        let shards = self.backend.shards().await?;
+        let counts = (0..self.shard_count)
+            .map(|shard_id| shards.get(&shard_id).copied())
+            .collect::<Option<Vec<u32>>>();
+        let counts = match counts {
+            Some(counts) => counts,
+            None => {
+                event!(tracing::Level::DEBUG, reported = shards.len(), shard_count = self.shard_count, "not posting until every shard is reported");
+                return Ok(false);
+            }
+        };
+        self.client
+            .post_bot_stats(StatsUpdate::shards(counts).with_shard_count(self.shard_count))
+            .await
+            .map_err(ClusterError::Post)?;
+        Ok(true)
+    }
+
+
+    /// Renews this process's shard counts and posts the cluster's every `interval` forever. Errors are
+    /// logged with the `tracing` feature and otherwise ignored, so one failed post doesn't stop the
+    /// next.
+    /// ## Examples
+    /// ```
+    /// let coordinator = Arc::new(coordinator);
+    /// let runner = coordinator.clone();
+    /// tokio::spawn(async move { runner.run(Duration::from_secs(5 * 60)).await });
+    /// ```
+    pub async fn run(&self, interval: Duration) {
+        loop {
+            let result = match self.publish().await {
+                Ok(()) => self.post().await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                event!(tracing::Level::WARN, error = %e, "posting the cluster's stats failed");
+            }
+            crate::rt::sleep(interval).await;
+        }
+    }
+
+
+    async fn publish(&self) -> Result<(), ClusterError> {
+        let shards = self.shards.lock().unwrap().clone();
+        self.backend.report(&self.process_id, &shards, self.ttl).await
+    }
+}
+
+
+/// Where the processes of a cluster share their shard counts and elect the one which posts them.
+/// Implement this to coordinate through your own database or message bus.
+#[async_trait]
+pub trait ClusterBackend: Send + Sync {
+    /// Saves the server counts of the shards run by `process`, replacing the ones it saved before.
+    /// They're forgotten after `ttl` unless saved again.
+    async fn report(&self, process: &str, shards: &BTreeMap<u32, u32>, ttl: Duration) -> Result<(), ClusterError>;
+
+    /// The server count of every shard saved by a process whose counts haven't been forgotten.
+    async fn shards(&self) -> Result<BTreeMap<u32, u32>, ClusterError>;
+
+    /// Makes `process` the poster for `ttl` if no process is, or extends its term if it already is.
+    /// Returns whether `process` is the poster.
+    async fn elect(&self, process: &str, ttl: Duration) -> Result<bool, ClusterError>;
+}
+
+
+/// Coordinates clusters running in one process, such as a bot running each group of shards in its own
+/// task. Clones share the same state.
+#[derive(Clone, Default)]
+pub struct MemoryCluster {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    reports: HashMap<String, (BTreeMap<u32, u32>, Instant)>,
+    poster: Option<(String, Instant)>,
+}
+
+impl MemoryCluster {
+    pub fn new() -> MemoryCluster {
+        MemoryCluster::default()
+    }
+}
+#[async_trait]
+impl ClusterBackend for MemoryCluster {
+    async fn report(&self, process: &str, shards: &BTreeMap<u32, u32>, ttl: Duration) -> Result<(), ClusterError> {
+        let expires_at = Instant::now() + ttl;
+        self.state.lock().unwrap().reports.insert(process.to_string(), (shards.clone(), expires_at));
+        Ok(())
+    }
+
+
+    async fn shards(&self) -> Result<BTreeMap<u32, u32>, ClusterError> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        state.reports.retain(|_, (_, expires_at)| *expires_at > now);
+        Ok(state.reports.values().flat_map(|(shards, _)| shards.clone()).collect())
+    }
+
+
+    async fn elect(&self, process: &str, ttl: Duration) -> Result<bool, ClusterError> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let elected = match &state.poster {
+            Some((poster, expires_at)) => poster == process || *expires_at <= now,
+            None => true,
+        };
+        if elected {
+            state.poster = Some((process.to_string(), now + ttl));
+        }
+        Ok(elected)
+    }
+}
+
+
+/// An error from coordinating a cluster's stats.
+#[derive(Debug)]
+pub enum ClusterError {
+    /// The backend shared by the processes failed, such as a lost database connection.
+    Backend(Box<dyn Error + Send + Sync>),
+    /// Posting the combined stats to top.gg failed.
+    Post(TopggError),
+    /// A shard was reported which is outside of the cluster's shard count.
+    InvalidShard { shard_id: u32, shard_count: u32 },
+}
+impl ClusterError {
+    /// Wraps an error from a backend.
+    pub fn backend(source: impl Into<Box<dyn Error + Send + Sync>>) -> ClusterError {
+        ClusterError::Backend(source.into())
+    }
+}
+impl std::fmt::Display for ClusterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClusterError::Backend(e) => write!(f, "cluster backend failed: {}", e),
+            ClusterError::Post(e) => write!(f, "posting the cluster's stats failed: {}", e),
+            ClusterError::InvalidShard { shard_id, shard_count } => {
+                write!(f, "shard {} is out of range for {} shards", shard_id, shard_count)
+            }
+        }
+    }
+}
+impl Error for ClusterError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ClusterError::Backend(e) => Some(&**e),
+            ClusterError::Post(e) => Some(e),
+            ClusterError::InvalidShard { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use ::redis::aio::ConnectionManager;
+use ::redis::Script;
+use async_trait::async_trait;
+
+use super::{ClusterBackend, ClusterError};
+
+
+
+/// Makes `ARGV[1]` the poster for `ARGV[2]` milliseconds if nobody is, or extends its term if it
+/// already is. Returns 1 if it's the poster.
+const ELECT: &str = r#"
+local poster = redis.call('GET', KEYS[1])
+if not poster then
+    redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2])
+    return 1
+end
+if poster == ARGV[1] then
+    redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    return 1
+end
+return 0
+"#;
+
+
+/// Coordinates a cluster through Redis. Each process's shard counts are a hash which Redis deletes by
+/// itself when the process stops renewing it, and the poster is a key holding its process ID. Needs the
+/// `redis-cluster` feature.
+/// ## Examples
+/// ```
+/// use topgg::cluster::{RedisCluster, StatsCoordinator};
+///
+/// let backend = RedisCluster::connect("redis://127.0.0.1/").await?;
+/// let coordinator = StatsCoordinator::new(client, backend, shard_count);
+/// ```
+#[derive(Clone)]
+pub struct RedisCluster {
+    conn: ConnectionManager,
+    prefix: String,
+    elect: Script,
+}
+impl RedisCluster {
+    /// Connects to the Redis server at `url`, reconnecting by itself if the connection drops.
+    pub async fn connect(url: &str) -> Result<RedisCluster, ClusterError> {
+        let client = ::redis::Client::open(url).map_err(ClusterError::backend)?;
+        let conn = ConnectionManager::new(client).await.map_err(ClusterError::backend)?;
+        Ok(RedisCluster::new(conn))
+    }
+
+
+    /// Returns a backend using an existing connection.
+    pub fn new(conn: ConnectionManager) -> RedisCluster {
+        RedisCluster {
+            conn,
+            prefix: "topgg:cluster".to_string(),
+            elect: Script::new(ELECT),
+        }
+    }
+
+
+    /// Sets what the keys start with, `topgg:cluster` by default, so several bots can share a database.
+    pub fn prefix(mut self, prefix: &str) -> RedisCluster {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+
+    fn shards_key(&self, process: &str) -> String {
+        format!("{}:shards:{}", self.prefix, process)
+    }
+}
+#[async_trait]
+impl ClusterBackend for RedisCluster {
+    async fn report(&self, process: &str, shards: &BTreeMap<u32, u32>, ttl: Duration) -> Result<(), ClusterError> {
+        let key = self.shards_key(process);
+        let mut pipe = ::redis::pipe();
+        pipe.atomic().cmd("DEL").arg(&key).ignore();
+        if !shards.is_empty() {
+            let counts = shards.iter().map(|(shard_id, count)| (*shard_id, *count)).collect::<Vec<_>>();
+            pipe.cmd("HSET").arg(&key).arg(counts).ignore();
+            pipe.cmd("PEXPIRE").arg(&key).arg(ttl.as_millis() as u64).ignore();
+        }
+        pipe.query_async::<_, ()>(&mut self.conn.clone())
+            .await
+            .map_err(ClusterError::backend)
+    }
+
+
+    async fn shards(&self) -> Result<BTreeMap<u32, u32>, ClusterError> {
+        let mut conn = self.conn.clone();
+        let pattern = self.shards_key("*");
+        let mut keys = Vec::new();
+        let mut cursor = 0u64;
+        loop {
+            let (next, batch): (u64, Vec<String>) = ::redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(100)
+                .query_async(&mut conn)
+                .await
+                .map_err(ClusterError::backend)?;
+            keys.extend(batch);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        let mut shards = BTreeMap::new();
+        for key in keys {
+            // The key may have run out since it was listed, which reads as empty.
+            let counts: HashMap<u32, u32> = ::redis::cmd("HGETALL")
+                .arg(&key)
+                .query_async(&mut conn)
+                .await
+                .map_err(ClusterError::backend)?;
+            shards.extend(counts);
+        }
+        Ok(shards)
+    }
+
+
+    async fn elect(&self, process: &str, ttl: Duration) -> Result<bool, ClusterError> {
+        let elected: u32 = self.elect
+            .key(format!("{}:poster", self.prefix))
+            .arg(process)
+            .arg(ttl.as_millis() as u64)
+            .invoke_async(&mut self.conn.clone())
+            .await
+            .map_err(ClusterError::backend)?;
+        Ok(elected == 1)
+    }
+}
@@ -0,0 +1,52 @@
+//! Rewarding and thanking voters on Discord, for bots made with serenity or twilight.
+
+use std::error::Error;
+use async_trait::async_trait;
+
+use crate::Snowflake;
+
+#[cfg(feature = "votes")]
+mod roles;
+#[cfg(feature = "votes")]
+pub use roles::{RewardError, RoleRewarder};
+mod thanks;
+pub use thanks::{DmError, VoteThanks};
+#[cfg(feature = "serenity")]
+mod serenity;
+#[cfg(feature = "twilight")]
+mod twilight;
+
+
+
+/// A Discord HTTP client which can give members roles and take them away. It's implemented for
+/// serenity's `Arc<Http>` with the `serenity` feature and twilight's `Arc<Client>` with the `twilight`
+/// feature.
+#[async_trait]
+pub trait RoleClient: Send + Sync {
+    /// Gives the member the role.
+    async fn add_role(&self, guild_id: Snowflake, user_id: Snowflake, role_id: Snowflake) -> Result<(), Box<dyn Error + Send + Sync>>;
+
+    /// Takes the role away from the member.
+    async fn remove_role(&self, guild_id: Snowflake, user_id: Snowflake, role_id: Snowflake) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+
+/// A Discord HTTP client which can send users direct messages. It's implemented for the same clients
+/// as `RoleClient`.
+#[async_trait]
+pub trait DmClient: Send + Sync {
+    /// Sends `content` to the user in a direct message.
+    async fn send_dm(&self, user_id: Snowflake, content: &str) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+
+/// Returned by the `RoleClient` and `DmClient` implementations for an ID of 0, which Discord never
+/// gives out and which serenity and twilight can't hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidId;
+impl std::fmt::Display for InvalidId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("a Discord ID can't be 0")
+    }
+}
+impl Error for InvalidId {}
@@ -0,0 +1,155 @@
+use std::error::Error;
+use std::time::Duration;
+use futures::future::{self, Either};
+use futures::{Stream, StreamExt};
+
+use super::RoleClient;
+use crate::votes::{MemoryStore, VoteManager, VoteStore, VoteStoreError};
+use crate::webhook::Event;
+use crate::Snowflake;
+
+
+
+/// Gives voters a role in a server and takes it away once their vote runs out, which it tracks with a
+/// `VoteManager`. The user has to be a member of the server for the role to be given.
+/// ## Examples
+/// ```
+/// use topgg::discord::RoleRewarder;
+///
+/// // in serenity's ready handler, or with twilight's `Arc<twilight_http::Client>`
+/// let rewarder = RoleRewarder::new(ctx.http.clone(), guild_id, voter_role_id);
+/// let (events, _handle) = topgg::WebhookClient::start(3030, auth)?;
+/// tokio::spawn(async move { rewarder.run(events).await });
+/// ```
+pub struct RoleRewarder<C, S = MemoryStore> {
+    client: C,
+    guild_id: Snowflake,
+    role_id: Snowflake,
+    votes: VoteManager<S>,
+    check_interval: Duration,
+}
+impl<C: RoleClient> RoleRewarder<C, MemoryStore> {
+    /// Returns a rewarder giving `role_id` in `guild_id`, keeping track of votes in memory. Roles
+    /// given before a restart are then never taken away, so use `with_votes` with a lasting store for
+    /// anything but trying it out.
+    pub fn new(client: C, guild_id: impl Into<Snowflake>, role_id: impl Into<Snowflake>) -> RoleRewarder<C, MemoryStore> {
+        RoleRewarder::with_votes(client, guild_id, role_id, VoteManager::new())
+    }
+}
+impl<C: RoleClient, S: VoteStore> RoleRewarder<C, S> {
+    /// Returns a rewarder which keeps track of votes with the given manager.
+    /// ## Examples
+    /// ```
+    /// let votes = VoteManager::with_store(SqliteStore::open("votes.db")?);
+    /// let rewarder = RoleRewarder::with_votes(ctx.http.clone(), guild_id, voter_role_id, votes);
+    /// ```
+    pub fn with_votes(client: C, guild_id: impl Into<Snowflake>, role_id: impl Into<Snowflake>, votes: VoteManager<S>) -> RoleRewarder<C, S> {
+        RoleRewarder {
+            client,
+            guild_id: guild_id.into(),
+            role_id: role_id.into(),
+            votes,
+            check_interval: Duration::from_secs(60),
+        }
+    }
+
+
+    /// Sets how often `run` checks for votes which ran out, once a minute by default. A role is taken
+    /// away up to this long after the vote ran out.
+    pub fn check_interval(mut self, interval: Duration) -> RoleRewarder<C, S> {
+        self.check_interval = interval;
+        self
+    }
+
+
+    /// The manager keeping track of votes, such as for checking whether a user has voted.
+    pub fn votes(&self) -> &VoteManager<S> {
+        &self.votes
+    }
+
+
+    /// Records the vote and gives the voter the role. Test webhooks are ignored.
+    pub async fn handle(&self, event: &Event) -> Result<(), RewardError> {
+        if let Event::Test(_) = event {
+            return Ok(());
+        }
+        self.votes.ingest(event).await.map_err(RewardError::Store)?;
+        self.grant(event.user()).await
+    }
+
+
+    /// Gives the user the role.
+    pub async fn grant(&self, user_id: impl Into<Snowflake>) -> Result<(), RewardError> {
+        self.client
+            .add_role(self.guild_id, user_id.into(), self.role_id)
+            .await
+            .map_err(RewardError::Discord)
+    }
+
+
+    /// Takes the role away from the user.
+    pub async fn revoke(&self, user_id: impl Into<Snowflake>) -> Result<(), RewardError> {
+        self.client
+            .remove_role(self.guild_id, user_id.into(), self.role_id)
+            .await
+            .map_err(RewardError::Discord)
+    }
+
+
+    /// Handles every event from `events` and takes the role away from users whose vote ran out,
+    /// forever. Failures to give or take a role are logged with the `tracing` feature and otherwise
+    /// skipped. Only returns if the vote store fails while checking for votes which ran out.
+    pub async fn run<E: Stream<Item = Event>>(&self, events: E) -> Result<(), VoteStoreError> {
+        let mut expirations = self.votes.expirations();
+        let handle = async {
+            futures::pin_mut!(events);
+            while let Some(event) = events.next().await {
+                if let Err(e) = self.handle(&event).await {
+                    event!(tracing::Level::WARN, error = %e, "rewarding a vote failed");
+                }
+            }
+            // Roles still have to be taken away after the events end.
+            future::pending::<()>().await;
+        };
+        let revoke = async {
+            while let Some(expired) = expirations.next().await {
+                if let Err(e) = self.revoke(expired.user_id).await {
+                    event!(tracing::Level::WARN, error = %e, "taking away a vote reward failed");
+                }
+            }
+        };
+        let work = future::join(handle, revoke);
+        let watch = self.votes.watch_expirations(self.check_interval);
+        futures::pin_mut!(work, watch);
+        match future::select(work, watch).await {
+            Either::Left(_) => Ok(()),
+            Either::Right((result, _)) => result,
+        }
+    }
+}
+
+
+/// An error from a `RoleRewarder`.
+#[derive(Debug)]
+pub enum RewardError {
+    /// The vote couldn't be recorded.
+    Store(VoteStoreError),
+    /// Discord refused to give or take away the role, such as when the bot's role is below it.
+    Discord(Box<dyn Error + Send + Sync>),
+}
+impl std::fmt::Display for RewardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RewardError::Store(e) => write!(f, "recording the vote failed: {}", e),
+            RewardError::Discord(e) => write!(f, "changing the role failed: {}", e),
+        }
+    }
+}
+impl Error for RewardError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RewardError::Store(e) => Some(e),
+            RewardError::Discord(e) => Some(&**e),
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::sync::Arc;
+use async_trait::async_trait;
+use serenity::http::Http;
+use serenity::model::id::{GuildId, RoleId, UserId};
+
+use super::{DmClient, InvalidId, RoleClient};
+use crate::Snowflake;
+
+
+
+#[async_trait]
+impl RoleClient for Arc<Http> {
+    async fn add_role(&self, guild_id: Snowflake, user_id: Snowflake, role_id: Snowflake) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (guild_id, user_id, role_id) = ids(guild_id, user_id, role_id)?;
+        self.add_member_role(guild_id, user_id, role_id, Some("Voted on top.gg")).await?;
+        Ok(())
+    }
+
+
+    async fn remove_role(&self, guild_id: Snowflake, user_id: Snowflake, role_id: Snowflake) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (guild_id, user_id, role_id) = ids(guild_id, user_id, role_id)?;
+        self.remove_member_role(guild_id, user_id, role_id, Some("top.gg vote ran out")).await?;
+        Ok(())
+    }
+}
+
+
+#[async_trait]
+impl DmClient for Arc<Http> {
+    async fn send_dm(&self, user_id: Snowflake, content: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let channel = UserId::new(non_zero(user_id)?).create_dm_channel(&**self).await?;
+        channel.id.say(&**self, content).await?;
+        Ok(())
+    }
+}
+
+
+fn ids(guild_id: Snowflake, user_id: Snowflake, role_id: Snowflake) -> Result<(GuildId, UserId, RoleId), InvalidId> {
+    Ok((GuildId::new(non_zero(guild_id)?), UserId::new(non_zero(user_id)?), RoleId::new(non_zero(role_id)?)))
+}
+
+
+/// The ID as a `u64`, checked first since serenity's IDs panic on 0.
+fn non_zero(id: Snowflake) -> Result<u64, InvalidId> {
+    match id.get() {
+        0 => Err(InvalidId),
+        id => Ok(id),
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use futures::{Stream, StreamExt};
+
+use super::DmClient;
+use crate::webhook::{vote_value, Event};
+use crate::Snowflake;
+
+
+
+/// Sends voters a thank-you direct message. The message is made from a template where these are
+/// replaced:
+/// * `{user}` - a mention of the user who voted
+/// * `{user_id}` - the ID of the user who voted
+/// * `{target}` - a mention of the bot, or the ID of the server, that was voted for
+/// * `{target_id}` - the ID of the bot or server that was voted for
+/// * `{points}` - how many points the vote is worth, 2 on weekends and 1 otherwise
+///
+/// A user is only messaged once in each cooldown, so top.gg retrying a delivery doesn't send the
+/// message twice. Users who don't share a server with the bot or have DMs turned off can't be messaged.
+/// ## Examples
+/// ```
+/// use topgg::discord::VoteThanks;
+///
+/// let thanks = VoteThanks::new(ctx.http.clone(), "Thanks for voting for {target}, {user}!");
+/// let (events, _handle) = topgg::WebhookClient::start(3030, auth)?;
+/// tokio::spawn(async move { thanks.run(events).await });
+/// ```
+pub struct VoteThanks<C> {
+    client: C,
+    template: String,
+    cooldown: Duration,
+    sent: Mutex<HashMap<Snowflake, Instant>>,
+}
+impl<C: DmClient> VoteThanks<C> {
+    /// Returns a helper sending messages made from `template` with the given client.
+    pub fn new(client: C, template: impl Into<String>) -> VoteThanks<C> {
+        VoteThanks {
+            client,
+            template: template.into(),
+            cooldown: Duration::from_secs(60 * 60),
+            sent: Mutex::new(HashMap::new()),
+        }
+    }
+
+
+    /// Sets how long after messaging a user they won't be messaged again, an hour by default. A
+    /// cooldown of 12 hours only thanks each user once per vote they can make.
+    pub fn cooldown(mut self, cooldown: Duration) -> VoteThanks<C> {
+        self.cooldown = cooldown;
+        self
+    }
+
+
+    /// Messages the user who voted. Returns `Ok(false)` if nothing was sent because it's a test or the
+    /// user was messaged within the cooldown.
+    pub async fn handle(&self, event: &Event) -> Result<bool, DmError> {
+        let (user, target, target_mention, points) = match event {
+            Event::BotVote(vote) => (vote.user, vote.bot, format!("<@{}>", vote.bot), vote.vote_value()),
+            Event::GuildVote(vote) => (vote.user, vote.guild, vote.guild.to_string(), vote_value(false)),
+            Event::Test(_) => return Ok(false),
+        };
+
+        let now = Instant::now();
+        {
+            let mut sent = self.sent.lock().unwrap();
+            sent.retain(|_, at| now.duration_since(*at) < self.cooldown);
+            if sent.contains_key(&user) {
+                return Ok(false);
+            }
+            // Taken before sending, so a retry arriving while the message is being sent is skipped too.
+            sent.insert(user, now);
+        }
+
+        let content = self
+            .template
+            .replace("{user_id}", &user.to_string())
+            .replace("{user}", &format!("<@{}>", user))
+            .replace("{target_id}", &target.to_string())
+            .replace("{target}", &target_mention)
+            .replace("{points}", &points.to_string());
+        match self.client.send_dm(user, &content).await {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                // Let the next delivery try again.
+                self.sent.lock().unwrap().remove(&user);
+                Err(DmError { source: e })
+            }
+        }
+    }
+
+
+    /// Messages the voter of every event from `events` until it ends. Failures are logged with the
+    /// `tracing` feature and otherwise skipped.
+    pub async fn run<S: Stream<Item = Event>>(&self, events: S) {
+        futures::pin_mut!(events);
+        while let Some(event) = events.next().await {
+            if let Err(e) = self.handle(&event).await {
+                event!(tracing::Level::WARN, error = %e, "thanking a voter failed");
+            }
+        }
+    }
+}
+
+
+/// An error from sending a direct message, such as the user having them turned off.
+#[derive(Debug)]
+pub struct DmError {
+    source: Box<dyn Error + Send + Sync>,
+}
+impl std::fmt::Display for DmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "sending the direct message failed: {}", self.source)
+    }
+}
+impl Error for DmError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.source)
+    }
+}
@@ -0,0 +1,45 @@
+use std::error::Error;
+use std::sync::Arc;
+use async_trait::async_trait;
+use twilight_http::request::AuditLogReason;
+use twilight_http::Client;
+use twilight_model::id::Id;
+
+use super::{DmClient, InvalidId, RoleClient};
+use crate::Snowflake;
+
+
+
+#[async_trait]
+impl RoleClient for Arc<Client> {
+    async fn add_role(&self, guild_id: Snowflake, user_id: Snowflake, role_id: Snowflake) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.add_guild_member_role(id(guild_id)?, id(user_id)?, id(role_id)?)
+            .reason("Voted on top.gg")?
+            .await?;
+        Ok(())
+    }
+
+
+    async fn remove_role(&self, guild_id: Snowflake, user_id: Snowflake, role_id: Snowflake) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.remove_guild_member_role(id(guild_id)?, id(user_id)?, id(role_id)?)
+            .reason("top.gg vote ran out")?
+            .await?;
+        Ok(())
+    }
+}
+
+
+#[async_trait]
+impl DmClient for Arc<Client> {
+    async fn send_dm(&self, user_id: Snowflake, content: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let channel = self.create_private_channel(id(user_id)?).await?.model().await?;
+        self.create_message(channel.id).content(content)?.await?;
+        Ok(())
+    }
+}
+
+
+/// Converts the ID, checked first since twilight's `Id::new` panics on 0.
+fn id<T>(id: Snowflake) -> Result<Id<T>, InvalidId> {
+    Id::new_checked(id.get()).ok_or(InvalidId)
+}
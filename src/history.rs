@@ -0,0 +1,187 @@
+//! Recording how a bot's points and server count change over time, for charting its growth without a
+//! separate collector. Needs the `history` feature.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::ops::{Bound, RangeBounds};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::TopggApi;
+
+
+
+/// Takes snapshots of your bot's points, monthly points and server count and saves them in a
+/// `HistoryStore`, in memory unless another is given with `with_store`.
+/// ## Examples
+/// ```
+/// use topgg::history::HistoryRecorder;
+///
+/// let recorder = Arc::new(HistoryRecorder::new(client));
+/// let runner = recorder.clone();
+/// tokio::spawn(async move { runner.run(Duration::from_secs(60 * 60)).await });
+///
+/// // later
+/// let week_ago = SystemTime::now() - Duration::from_secs(7 * 24 * 60 * 60);
+/// for snapshot in recorder.history(week_ago..).await? {
+///     println!("{:?}: {} points", snapshot.at, snapshot.points);
+/// }
+/// ```
+pub struct HistoryRecorder<S = MemoryHistory> {
+    client: Box<dyn TopggApi>,
+    store: S,
+}
+impl HistoryRecorder<MemoryHistory> {
+    /// Returns a recorder which keeps the snapshots in memory.
+    pub fn new(client: impl TopggApi + 'static) -> HistoryRecorder<MemoryHistory> {
+        HistoryRecorder::with_store(client, MemoryHistory::new())
+    }
+}
+impl<S: HistoryStore> HistoryRecorder<S> {
+    /// Returns a recorder which saves the snapshots in `store`.
+    pub fn with_store(client: impl TopggApi + 'static, store: S) -> HistoryRecorder<S> {
+        HistoryRecorder {
+            client: Box::new(client),
+            store,
+        }
+    }
+
+
+    /// Fetches the bot's points and server count now and saves them. Returns the snapshot, or `None`
+    /// if top.gg couldn't be reached.
+    pub async fn record(&self) -> Result<Option<Snapshot>, HistoryError> {
+        let (bot, stats) = futures::join!(self.client.my_bot(), self.client.my_bot_stats());
+        let bot = match bot {
+            Some(bot) => bot,
+            None => return Ok(None),
+        };
+        let snapshot = Snapshot {
+            at: SystemTime::now(),
+            points: bot.points,
+            monthly_points: bot.monthly_points,
+            server_count: stats.and_then(|stats| stats.server_count),
+        };
+        self.store.record(snapshot).await?;
+        Ok(Some(snapshot))
+    }
+
+
+    /// Records a snapshot every `interval` forever. Failures are logged with the `tracing` feature and
+    /// otherwise skipped, so a gap in the history is left instead of stopping.
+    pub async fn run(&self, interval: Duration) {
+        loop {
+            match self.record().await {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    event!(tracing::Level::WARN, "couldn't fetch the bot for its history");
+                }
+                Err(e) => {
+                    event!(tracing::Level::WARN, error = %e, "couldn't save the bot's history");
+                }
+            }
+            crate::rt::sleep(interval).await;
+        }
+    }
+
+
+    /// The snapshots taken within `range`, oldest first.
+    /// ## Examples
+    /// ```
+    /// let day_ago = SystemTime::now() - Duration::from_secs(24 * 60 * 60);
+    /// let today = recorder.history(day_ago..).await?;
+    /// let everything = recorder.history(..).await?;
+    /// ```
+    pub async fn history(&self, range: impl RangeBounds<SystemTime>) -> Result<Vec<Snapshot>, HistoryError> {
+        self.store.between(range.start_bound().cloned(), range.end_bound().cloned()).await
+    }
+
+
+    /// The most recent snapshot, if any have been taken.
+    pub async fn latest(&self) -> Result<Option<Snapshot>, HistoryError> {
+        Ok(self.history(..).await?.pop())
+    }
+}
+
+
+/// The bot's standing on top.gg at one point in time.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot {
+    /// When it was taken.
+    pub at: SystemTime,
+    /// The bot's votes of all time.
+    pub points: u64,
+    /// The bot's votes this month.
+    pub monthly_points: u64,
+    /// The server count last posted to top.gg, if one has been.
+    pub server_count: Option<u32>,
+}
+
+
+/// Where a `HistoryRecorder` keeps its snapshots. Implement this to keep them in your own database.
+#[async_trait]
+pub trait HistoryStore: Send + Sync {
+    /// Saves a snapshot.
+    async fn record(&self, snapshot: Snapshot) -> Result<(), HistoryError>;
+
+    /// The snapshots taken between `from` and `to`, oldest first.
+    async fn between(&self, from: Bound<SystemTime>, to: Bound<SystemTime>) -> Result<Vec<Snapshot>, HistoryError>;
+}
+
+
+/// Keeps snapshots in memory, so they're lost when the process stops.
+#[derive(Default)]
+pub struct MemoryHistory {
+    snapshots: Mutex<BTreeMap<SystemTime, Snapshot>>,
+}
+impl MemoryHistory {
+    pub fn new() -> MemoryHistory {
+        MemoryHistory::default()
+    }
+}
+#[async_trait]
+impl HistoryStore for MemoryHistory {
+    async fn record(&self, snapshot: Snapshot) -> Result<(), HistoryError> {
+        self.snapshots.lock().unwrap().insert(snapshot.at, snapshot);
+        Ok(())
+    }
+
+
+    async fn between(&self, from: Bound<SystemTime>, to: Bound<SystemTime>) -> Result<Vec<Snapshot>, HistoryError> {
+        let range = (from, to);
+        Ok(self
+            .snapshots
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|snapshot| range.contains(&snapshot.at))
+            .copied()
+            .collect())
+    }
+}
+
+
+/// An error from the storage behind a `HistoryStore`, such as a lost database connection.
+#[derive(Debug)]
+pub struct HistoryError {
+    source: Box<dyn Error + Send + Sync>,
+}
+impl HistoryError {
+    /// Wraps an error from a store's backend.
+    pub fn new(source: impl Into<Box<dyn Error + Send + Sync>>) -> HistoryError {
+        HistoryError {
+            source: source.into(),
+        }
+    }
+}
+impl std::fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "history store failed: {}", self.source)
+    }
+}
+impl Error for HistoryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.source)
+    }
+}
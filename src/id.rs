@@ -0,0 +1,145 @@
+//! Discord IDs, which top.gg sends as strings but are handled as `Snowflake`s wrapping a `u64`.
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+
+
+/// The ID of a Discord user, bot or server. It's written to JSON as a string like top.gg does, and
+/// read from either a string or an integer.
+///
+/// Methods taking an ID accept anything that converts into one: a `u64`, or the ID types from serenity
+/// and twilight when the `serenity` or `twilight` feature is enabled. IDs written as strings, like the
+/// ones in Discord's mentions and URLs, are parsed with `str::parse`.
+/// ## Examples
+/// ```
+/// use topgg::Snowflake;
+///
+/// let id = Snowflake::from(264811613708746752);
+/// assert_eq!(id.to_string(), "264811613708746752");
+/// assert_eq!(u64::from(id), 264811613708746752);
+/// assert_eq!("264811613708746752".parse::<Snowflake>(), Ok(id));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Snowflake(pub u64);
+impl Snowflake {
+    /// The ID as a `u64`.
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+impl From<u64> for Snowflake {
+    fn from(id: u64) -> Snowflake {
+        Snowflake(id)
+    }
+}
+#[cfg(feature = "serenity")]
+impl From<serenity::model::id::UserId> for Snowflake {
+    fn from(id: serenity::model::id::UserId) -> Snowflake {
+        Snowflake(id.get())
+    }
+}
+#[cfg(feature = "serenity")]
+impl From<serenity::model::id::GuildId> for Snowflake {
+    fn from(id: serenity::model::id::GuildId) -> Snowflake {
+        Snowflake(id.get())
+    }
+}
+#[cfg(feature = "serenity")]
+impl From<serenity::model::id::ApplicationId> for Snowflake {
+    fn from(id: serenity::model::id::ApplicationId) -> Snowflake {
+        Snowflake(id.get())
+    }
+}
+#[cfg(feature = "serenity")]
+impl From<serenity::model::id::RoleId> for Snowflake {
+    fn from(id: serenity::model::id::RoleId) -> Snowflake {
+        Snowflake(id.get())
+    }
+}
+#[cfg(feature = "twilight")]
+impl<T> From<twilight_model::id::Id<T>> for Snowflake {
+    fn from(id: twilight_model::id::Id<T>) -> Snowflake {
+        Snowflake(id.get())
+    }
+}
+impl From<Snowflake> for u64 {
+    fn from(id: Snowflake) -> u64 {
+        id.0
+    }
+}
+impl PartialEq<u64> for Snowflake {
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+impl fmt::Display for Snowflake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+impl FromStr for Snowflake {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Snowflake, ParseIntError> {
+        s.parse().map(Snowflake)
+    }
+}
+impl Serialize for Snowflake {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+impl<'de> Deserialize<'de> for Snowflake {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Snowflake, D::Error> {
+        deserializer.deserialize_any(IdVisitor)
+    }
+}
+
+
+struct IdVisitor;
+impl<'de> Visitor<'de> for IdVisitor {
+    type Value = Snowflake;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a discord ID as a string or integer")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Snowflake, E> {
+        Ok(Snowflake(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Snowflake, E> {
+        if v < 0 {
+            return Err(E::invalid_value(de::Unexpected::Signed(v), &self));
+        }
+        Ok(Snowflake(v as u64))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Snowflake, E> {
+        v.parse()
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+
+/// Deserializes an ID which may be missing, treating `null`, an empty string or anything else that
+/// isn't an ID as `None`. Use with `#[serde(default, deserialize_with = "crate::id::deserialize_option")]`.
+pub(crate) fn deserialize_option<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Snowflake>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Int(u64),
+        Str(String),
+        Other(serde::de::IgnoredAny),
+    }
+
+    Ok(match Option::<Raw>::deserialize(deserializer)? {
+        Some(Raw::Int(id)) => Some(Snowflake(id)),
+        Some(Raw::Str(id)) => id.parse().ok(),
+        Some(Raw::Other(_)) | None => None,
+    })
+}
@@ -0,0 +1,73 @@
+//! Receiving webhooks in an AWS Lambda function with [lambda_http](https://docs.rs/lambda_http), so
+//! votes can be handled without a server running all the time. Needs the `lambda` feature.
+
+use std::sync::Arc;
+use futures::future::BoxFuture;
+use futures::{Future, FutureExt};
+use lambda_http::http::header;
+use lambda_http::{Body, Error, Request, Response};
+
+use crate::webhook::{self, Event, WebhookError};
+
+
+
+/// Checks the authorization header of a webhook received by the function and parses it.
+/// ## Examples
+/// ```
+/// async fn vote(req: lambda_http::Request) -> Result<&'static str, lambda_http::Error> {
+///     let event = topgg::lambda::parse(&auth, &req)?;
+///     println!("{:?}", event);
+///     Ok("ok")
+/// }
+/// ```
+pub fn parse(auth: &str, req: &Request) -> Result<Event, WebhookError> {
+    let authorization = req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    webhook::parse(auth, authorization, req.body().as_ref())
+}
+
+
+/// Returns a handler for `lambda_http::run` which checks and parses each webhook before passing it
+/// to `handler`. Requests which aren't valid webhooks are refused with a 401 or 400, and if the handler
+/// fails top.gg is sent a 500 so it retries the vote later.
+/// ## Examples
+/// ```
+/// use lambda_http::{run, service_fn};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), lambda_http::Error> {
+///     let auth = std::env::var("TOPGG_WEBHOOK_AUTH")?;
+///     run(service_fn(topgg::lambda::handler(auth, |event| async move {
+///         println!("{:?}", event);
+///         Ok(())
+///     })))
+///     .await
+/// }
+/// ```
+pub fn handler<F, Fut>(auth: String, handler: F) -> impl Fn(Request) -> BoxFuture<'static, Result<Response<Body>, Error>>
+where
+    F: Fn(Event) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), Error>> + Send + 'static,
+{
+    let auth: Arc<str> = auth.into();
+    let handler = Arc::new(handler);
+    move |req: Request| {
+        let auth = auth.clone();
+        let handler = handler.clone();
+        async move {
+            let status = match parse(&auth, &req) {
+                Ok(event) => match handler(event).await {
+                    Ok(()) => 200,
+                    Err(_) => 500,
+                },
+                Err(e) => e.status(),
+            };
+            let res = Response::builder()
+                .status(status)
+                .body(Body::Empty)?;
+            Ok::<_, Error>(res)
+        }
+        .boxed()
+    }
+}
@@ -1,531 +1,312 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
-use std::num::NonZeroU32;
-use governor::{Quota, RateLimiter, clock, state};
 
-use futures_util::future;
-use warp::Filter;
-use futures::channel::mpsc;
-use tokio::task;
-
-
-
-const BASE_URL: &str = "https://top.gg/api";
-
-
-/// This is the top.gg API client. It houses the functions needed to interact with their API.
-pub struct Topgg {
-    bot_id: u64,
-    token: String,
-    client: reqwest::Client,
-    limiter: RateLimiter<state::direct::NotKeyed, state::InMemoryState, clock::DefaultClock>
+#[macro_use]
+mod macros;
+mod cdn;
+mod id;
+#[cfg(any(feature = "client", feature = "relay", feature = "forward"))]
+mod rt;
+pub use id::Snowflake;
+#[cfg(feature = "client")]
+mod client;
+#[cfg(feature = "client")]
+pub use client::{BotList, ConfigError, Middleware, MultiPoster, RateLimitStats, RequestSummary, ResponseMeta, StatsUpdate, Topgg, TopggApi, TopggBuilder, TopggError, TopggPool, VoterDiff, WithMeta, USER_AGENT};
+#[cfg(feature = "vcr")]
+pub use client::{Cassette, VcrMode};
+#[cfg(feature = "redis-ratelimit")]
+pub use client::RedisRateLimit;
+#[cfg(feature = "blocking")]
+pub use client::blocking;
+#[cfg(feature = "discordbotlist")]
+pub use client::DiscordBotList;
+#[cfg(feature = "discords")]
+pub use client::Discords;
+#[cfg(feature = "client")]
+pub mod cluster;
+#[cfg(feature = "history")]
+pub mod history;
+#[cfg(feature = "moka")]
+mod cache;
+#[cfg(feature = "moka")]
+pub use cache::CacheStats;
+pub mod webhook;
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "rocket")]
+pub mod rocket;
+#[cfg(feature = "tower")]
+pub mod tower;
+#[cfg(feature = "lambda")]
+pub mod lambda;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "votes")]
+pub mod votes;
+#[cfg(any(feature = "serenity", feature = "twilight"))]
+pub mod discord;
+pub use webhook::{Event, GuildWebhook, Vote, Webhook};
+#[cfg(feature = "webhook")]
+pub use webhook::{WebhookClient, WebhookHandle};
+
+
+
+/// The address of the top.gg website, which pages and vote links are built from.
+const TOPGG_URL: &str = "https://top.gg";
+
+
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Bot {
+    pub id: Snowflake,
+    pub username: String,
+    pub discriminator: String,
+    pub avatar: Option<String>,
+    pub def_avatar: String,
+    pub lib: String,
+    pub prefix: String,
+    pub short_desc: String,
+    pub long_desc: Option<String>,
+    pub tags: Vec<String>,
+    pub website: Option<String>,
+    pub support: Option<String>,
+    pub github: Option<String>,
+    pub owners: Vec<Snowflake>,
+    pub guilds: Vec<Snowflake>,
+    pub invite: Option<String>,
+    /// When the bot was approved on top.gg.
+    #[cfg(feature = "chrono")]
+    pub date: chrono::DateTime<chrono::Utc>,
+    /// When the bot was approved on top.gg, like `2021-03-23T18:26:14.578Z`. Enable the `chrono`
+    /// feature to have it parsed.
+    #[cfg(not(feature = "chrono"))]
+    pub date: String,
+    pub certified_bot: bool,
+    pub vanity: Option<String>,
+    pub points: u64,
+    pub monthly_points: u64,
+    pub donate_bot_guild_id: Option<Snowflake>
 }
-impl Topgg {
-    /// Returns a new client.
-    /// 
-    /// ## Arguments
-    /// * `bot_id` - The ID of your bot
-    /// * `token` - The top.gg token for that (or another valid) bot
-    /// 
-    /// ## Examples
-    /// ```
-    /// let client = topgg::Topgg::new(bot_id, token);
-    /// // Do stuff with the client
-    /// let votes = client.votes().await.unwrap();
-    /// ```
-    /// 
-    pub fn new(bot_id: u64, token: String) -> Topgg {
-        Topgg {
-            bot_id: bot_id,
-            token: token,
-            client: reqwest::Client::new(),
-            limiter: RateLimiter::direct(
-                Quota::per_minute(NonZeroU32::new(60u32).unwrap())
-            )
-        }
-    }
-
-
-    /// A shortcut for getting the botinfo for your own bot.
+impl Bot {
+    /// The URL of the bot's avatar on Discord's CDN, or of the default avatar if it hasn't set one.
+    /// `size` is rounded up to a power of two between 16 and 4096.
     /// ## Examples
     /// ```
-    /// let bot_info = client.my_bot().await.unwrap();
+    /// let embed = CreateEmbed::new().thumbnail(bot.avatar_url(256));
     /// ```
-    pub async fn my_bot(&self) -> Option<Bot> {
-        self.bot(self.bot_id).await
+    pub fn avatar_url(&self, size: u16) -> String {
+        cdn::avatar_url(self.id, &self.discriminator, self.avatar.as_deref(), size)
     }
 
 
-    /// Gets the info for a bot given an ID. To get the info for your own bot `client.my_bot()` can be used as a shortcut.
-    /// ## Examples
-    /// ```
-    /// let bot_info = lient.bot(668701133069352961).await.unwrap();
-    /// ```
-    pub async fn bot(&self, bot_id: u64) -> Option<Bot> {
-        self.limiter.until_ready().await;
-        println!("requesting");
-        let url = format!("{}/bots/{}", BASE_URL, bot_id);
-        let res = self.client
-            .get(&url)
-            .header("Authorization", &self.token)
-            .send()
-            .await;
-        if res.is_err() {
-            return None;
-        }
-
-        let res = res
-            .unwrap()        
-            .json::<JsonBot>()
-            .await;
-        if res.is_err() {
-            return None;
-        }
-        let res = res.unwrap();
-
-        Some( Bot {
-            id: res.id.parse::<u64>().unwrap(),
-            username: res.username,
-            discriminator: res.discriminator,
-            avatar: res.avatar,
-            def_avatar: res.defAvatar,
-            lib: res.lib,
-            prefix: res.prefix,
-            short_desc: res.shortdesc,
-            long_desc: res.longdesc,
-            tags: res.tags,
-            website: res.website,
-            support: res.support,
-            github: res.github,
-            owners: res.owners.into_iter().map(|u| u.parse::<u64>().unwrap()).collect(),
-            guilds: res.guilds.into_iter().map(|u| u.parse::<u64>().unwrap()).collect(),
-            invite: res.invite,
-            date: res.date,
-            certified_bot: res.certifiedBot,
-            vanity: res.vanity,
-            points: res.points,
-            monthly_points: res.monthlyPoints,
-            donate_bot_guild_id: res.donatebotguildid.parse::<u64>().ok()
-        })
+    /// The URL of the bot's page on top.gg, using its ID.
+    pub fn page_url(&self) -> String {
+        format!("{}/bot/{}", TOPGG_URL, self.id)
     }
 
 
-    /// Gets the info for a user.
-    /// ## Examples
-    /// ```
-    /// client.user(195512978634833920).await.unwrap();
-    /// ```
-    pub async fn user(&self, user_id: u64) -> Option<User> {
-        self.limiter.until_ready().await;
-        let url = format!("{}/users/{}", BASE_URL, user_id);
-        let res = self.client
-            .get(&url)
-            .header("Authorization", &self.token)
-            .send()
-            .await;
-        if res.is_err() {
-            return None;
+    /// The URL of the bot's page on top.gg, using its vanity URL if it has one.
+    pub fn vanity_page_url(&self) -> String {
+        match self.vanity.as_deref().filter(|vanity| !vanity.is_empty()) {
+            Some(vanity) => format!("{}/bot/{}", TOPGG_URL, vanity),
+            None => self.page_url(),
         }
-
-        let res = res
-            .unwrap()        
-            .json::<JsonUser>()
-            .await;
-        if res.is_err() {
-            return None;
-        }
-        let res = res.unwrap();
-
-        Some( User {
-            id: res.id.parse::<u64>().unwrap(),
-            username: res.username,
-            discriminator: res.discriminator,
-            avatar:res.avatar,
-            def_avatar: res.defAvatar,
-            bio: res.bio,
-            banner: res.banner,
-            youtube: res.social.get("youtube").map(|r| r.parse::<String>().unwrap()),
-            reddit: res.social.get("reddit").map(|r| r.parse::<String>().unwrap()),
-            twitter: res.social.get("twitter").map(|r| r.parse::<String>().unwrap()),
-            instagram: res.social.get("instagram").map(|r| r.parse::<String>().unwrap()),
-            github: res.social.get("github").map(|r| r.parse::<String>().unwrap()),
-            color: res.color,
-            supporter: res.supporter,
-            certified_dev: res.certifiedDev,
-            moderator: res.r#mod,
-            web_moderator: res.webMod,
-            admin: res.admin,
-        })
     }
 
 
-    /// A shortcut for getting the votes for the bot that created the client.
+    /// The URL users vote for the bot on, using its ID.
     /// ## Examples
     /// ```
-    /// let votes = client.my_votes().await.unwrap();
+    /// msg.reply(&ctx, format!("Vote for me at {}", bot.vote_url())).await?;
     /// ```
-    pub async fn my_votes(&self) -> Option<Vec<u64>> {
-        self.votes(self.bot_id).await
+    pub fn vote_url(&self) -> String {
+        format!("{}/vote", self.page_url())
     }
 
 
-    /// Gets the user IDs of all the users that have voted on the bot_id.
-    /// ## Examples
-    /// ```
-    /// client.votes(668701133069352961).await.unwrap();
-    /// ```
-    pub async fn votes(&self, bot_id: u64) -> Option<Vec<u64>> {
-        self.limiter.until_ready().await;
-        let url = format!("{}/bots/{}/votes", BASE_URL, bot_id);
-        let res = self.client
-            .get(&url)
-            .header("Authorization", &self.token)
-            .send()
-            .await;
-        if res.is_err() {
-            return None;
-        }
-
-        let res = res
-            .unwrap()        
-            .json::<Vec<PartialJsonUser>>()
-            .await;
-        if res.is_err() {
-            return None;
-        }
-        let res = res.unwrap();
-
-        Some(
-            res.into_iter()
-                .map(|u| u.id.parse::<u64>().unwrap())
-                .collect()
-        )
+    /// The URL users vote for the bot on, using its vanity URL if it has one.
+    pub fn vanity_vote_url(&self) -> String {
+        format!("{}/vote", self.vanity_page_url())
     }
+}
 
 
-    /// A shortcut for checking if a user has voted for your own bot.
-    /// ## Examples
-    /// ```
-    /// let voted = client.voted_for_me(195512978634833920).await.unwrap();
-    /// ```
-    pub async fn voted_for_me(&self, user_id: u64) -> Option<bool> {
-        self.voted(self.bot_id, user_id).await
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct User {
+    pub id: Snowflake,
+    pub username: String,
+    pub discriminator: String,
+    pub avatar: Option<String>,
+    pub def_avatar: String,
+    pub bio: Option<String>,
+    pub banner: Option<String>,
+    pub social: Social,
+    pub color: Option<String>,
+    pub supporter: bool,
+    pub certified_dev: bool,
+    pub moderator: bool,
+    pub web_moderator: bool,
+    pub admin: bool,
+}
+impl User {
+    /// The URL of the user's avatar on Discord's CDN, or of the default avatar if they haven't set one.
+    /// `size` is rounded up to a power of two between 16 and 4096.
+    pub fn avatar_url(&self, size: u16) -> String {
+        cdn::avatar_url(self.id, &self.discriminator, self.avatar.as_deref(), size)
     }
 
 
-    /// Checks if a user has voted for the bot or not. Returns true if they have, false if they have not.
-    /// ## Examples
-    /// ```
-    /// let voted = client.voted(668701133069352961, 195512978634833920)
-    ///     .await
-    ///     .unwrap();
-    /// ```
-    pub async fn voted(&self, bot_id: u64, user_id: u64) -> Option<bool> {
-        self.limiter.until_ready().await;
-        let url = format!("{}/bots/{}/check?userId={}", BASE_URL, bot_id, user_id);
-        let res = self.client
-            .get(&url)
-            .header("Authorization", &self.token)
-            .send()
-            .await;
-        if res.is_err() {
-            return None;
+    /// The URL of the banner on the user's top.gg profile, if they have one. `size` is only used for
+    /// banners hosted on Discord's CDN.
+    pub fn banner_url(&self, size: u16) -> Option<String> {
+        let banner = self.banner.as_deref().filter(|banner| !banner.is_empty())?;
+        if banner.starts_with("http://") || banner.starts_with("https://") {
+            return Some(banner.to_string());
         }
+        Some(cdn::image_url("banners", self.id, banner, size))
+    }
 
-        let res = res
-            .unwrap()        
-            .json::<CheckVote>()
-            .await;
-        if res.is_err() {
-            return None;
-        }
-        let res = res.unwrap();
 
-        if res.voted == 0 {
-            return Some(false);
-        } else {
-            return Some(true);
-        }
+    /// The user's YouTube channel, a shortcut for `user.social.youtube`.
+    pub fn youtube(&self) -> Option<&str> {
+        self.social.youtube.as_deref()
     }
 
 
-    /// A shortcut for getting the bot stats of the bot that created the client.
-    /// ## Examples
-    /// ```
-    /// let stats = client.my_bot_stats().await.unwrap();
-    /// ```
-    pub async fn my_bot_stats(&self) -> Option<BotStats> {
-        self.get_bot_stats(self.bot_id).await
+    /// The user's Reddit username, a shortcut for `user.social.reddit`.
+    pub fn reddit(&self) -> Option<&str> {
+        self.social.reddit.as_deref()
     }
 
 
-    /// Gets the 'stats' of the bot, this includes the server count, shard count, and shards (servers per shard).
-    /// ## Examples
-    /// ```
-    /// client.get_bot_stats(Some(668701133069352961)).await.unwrap();
-    /// ```
-    pub async fn get_bot_stats(&self, bot_id: u64) -> Option<BotStats> {
-        self.limiter.until_ready().await;
-        let url = format!("{}/bots/{}/stats", BASE_URL, bot_id);
-        let res = self.client
-            .get(&url)
-            .header("Authorization", &self.token)
-            .send()
-            .await;
-        if res.is_err() {
-            return None;
-        }
+    /// The user's Twitter username, a shortcut for `user.social.twitter`.
+    pub fn twitter(&self) -> Option<&str> {
+        self.social.twitter.as_deref()
+    }
 
-        let res = res
-            .unwrap()        
-            .json::<BotStats>()
-            .await;
-        if res.is_err() {
-            return None;
-        }
-        let res = res.unwrap();
 
-        Some(res)
+    /// The user's Instagram username, a shortcut for `user.social.instagram`.
+    pub fn instagram(&self) -> Option<&str> {
+        self.social.instagram.as_deref()
     }
 
-    
-    /// This posts the stats for your bot. Useful if you want to update the server count on your top.gg bot page. You can omit from having a `server_count` if you use `shards` where it is a Vec of the number of servers per shard. `shard_id` is only applicable if you use `sever_count` and it tells top.gg the number of servers for that indexed shard.
-    /// ## Examples
-    /// ```
-    /// client.post_bot_stats(None, Some(vec![142, 532, 304]), None, None).await;
-    /// client.post_bot_stats(Some(142), None, Some(0), None).await;
-    /// client.post_bot_stats(Some(978), None, None, Some(3)).await;
-    /// ```
-    pub async fn post_bot_stats(
-        &self,
-        server_count: Option<u32>,
-        shards: Option<Vec<u32>>,
-        shard_id: Option<u32>,
-        shard_count: Option<u32>
-    ) -> Result<reqwest::Response, reqwest::Error> {
-        self.limiter.until_ready().await;
-        let url = format!("{}/bots/{}/stats", BASE_URL, self.bot_id);
-        self.client
-            .post(&url)
-            .header("Authorization", &self.token)
-            .json(&PostBotStats {
-                server_count: server_count,
-                shards: shards,
-                shard_id: shard_id,
-                shard_count: shard_count,
-            })
-            .send()
-            .await
+
+    /// The user's GitHub username, a shortcut for `user.social.github`.
+    pub fn github(&self) -> Option<&str> {
+        self.social.github.as_deref()
     }
 }
 
 
-
-pub struct WebhookClient;
-impl WebhookClient {
-    /// Starts listening to a port and filtering requests with a authentication string.
-    /// ## Examples
-    /// ```rust
-    /// use futures::StreamExt;
-    /// 
-    /// #[tokio::main]
-    /// async fn main() {
-    ///     let mut events = topgg::WebhookClient::start(3030, "a-very-secret-password".to_string());
-    ///     
-    ///     while let Some(msg) = events.next().await {
-    ///         println!("{:?}", msg)
-    ///     }
-    /// }
-    /// ```
-    pub fn start(port: u16, auth: String) -> mpsc::UnboundedReceiver<Webhook> {
-
-        let filter = warp::header::<String>("authorization")
-            .and_then(move |value| {
-                if value == auth {
-                    future::ok(())
-                } else {
-                    future::err(warp::reject::custom(Unauthorized))
-                }
-            })
-            .untuple_one();
-
-        let (event_send, event_read) = mpsc::unbounded();
-
-
-        let webhook = warp::post()
-            .and(filter)
-            .and(warp::body::json())
-            .map(move |hook: Webhook| {
-                event_send.unbounded_send(hook).unwrap();
-                warp::reply()
-            });
-        
-        task::spawn(async move {
-            warp::serve(webhook).run(([0, 0, 0, 0], port)).await;
-        });
-        
-        event_read
+/// The social accounts a user has linked on their top.gg profile. Networks top.gg added after this
+/// was written end up in `other`, keyed by the name top.gg uses for them.
+/// ## Examples
+/// ```
+/// for (network, account) in user.social.iter() {
+///     println!("{}: {}", network, account);
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(from = "HashMap<String, Option<String>>", into = "HashMap<String, String>")]
+pub struct Social {
+    pub youtube: Option<String>,
+    pub reddit: Option<String>,
+    pub twitter: Option<String>,
+    pub instagram: Option<String>,
+    pub github: Option<String>,
+    pub other: HashMap<String, String>,
+}
+impl Social {
+    /// Every linked account as `(network, account)`, the known networks first.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        let known = [
+            ("youtube", &self.youtube),
+            ("reddit", &self.reddit),
+            ("twitter", &self.twitter),
+            ("instagram", &self.instagram),
+            ("github", &self.github),
+        ];
+        IntoIterator::into_iter(known)
+            .filter_map(|(network, account)| Some((network, account.as_deref()?)))
+            .chain(self.other.iter().map(|(network, account)| (network.as_str(), account.as_str())))
     }
 }
-
-
-
-#[derive(Debug)]
-struct Unauthorized;
-impl warp::reject::Reject for Unauthorized {}
-impl std::fmt::Display for Unauthorized {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("Unauthorized")
+impl From<HashMap<String, Option<String>>> for Social {
+    fn from(mut map: HashMap<String, Option<String>>) -> Social {
+        // top.gg sends empty strings for accounts that aren't linked.
+        let mut take = |network: &str| map.remove(network).flatten().filter(|account| !account.is_empty());
+        Social {
+            youtube: take("youtube"),
+            reddit: take("reddit"),
+            twitter: take("twitter"),
+            instagram: take("instagram"),
+            github: take("github"),
+            other: map
+                .into_iter()
+                .filter_map(|(network, account)| Some((network, account.filter(|account| !account.is_empty())?)))
+                .collect(),
+        }
     }
 }
-impl std::error::Error for Unauthorized {}
-
-
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Webhook {
-    pub bot: String,
-    pub user: String,
-    #[serde(rename = "type")]
-    pub kind: String,
-    pub is_weekend: bool,
-    pub query: Option<String>,
+impl From<Social> for HashMap<String, String> {
+    fn from(social: Social) -> HashMap<String, String> {
+        social
+            .iter()
+            .map(|(network, account)| (network.to_string(), account.to_string()))
+            .collect()
+    }
 }
 
 
-
-
-#[allow(non_snake_case)]
-#[derive(Deserialize, Debug)]
-struct JsonBot {
-    id: String,
-    username: String,
-    discriminator: String,
-    avatar: Option<String>,
-    defAvatar: String, 
-    lib: String,
-    prefix: String,
-    shortdesc: String,
-    longdesc: Option<String>,
-    tags: Vec<String>,
-    website: Option<String>,
-    support: Option<String>,
-    github: Option<String>,
-    owners: Vec<String>,
-    guilds: Vec<String>,
-    invite: Option<String>,
-    date: String,
-    certifiedBot: bool,
-    vanity: Option<String>,
-    points: u64,
-    monthlyPoints: u64,
-    donatebotguildid: String
-}
-
-#[derive(Deserialize, Debug)]
-pub struct Bot {
-    pub id: u64,
-    pub username: String,
-    pub discriminator: String,
-    pub avatar: Option<String>,
-    pub def_avatar: String,
-    pub lib: String,
-    pub prefix: String,
+/// A Discord server listed on top.gg.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Server {
+    pub id: Snowflake,
+    pub name: String,
+    pub icon: Option<String>,
     pub short_desc: String,
     pub long_desc: Option<String>,
     pub tags: Vec<String>,
     pub website: Option<String>,
-    pub support: Option<String>,
-    pub github: Option<String>,
-    pub owners: Vec<u64>,
-    pub guilds: Vec<u64>,
     pub invite: Option<String>,
-    pub date: String,
-    pub certified_bot: bool,
     pub vanity: Option<String>,
     pub points: u64,
     pub monthly_points: u64,
-    pub donate_bot_guild_id: Option<u64>
 }
+impl Server {
+    /// The URL of the server's icon on Discord's CDN, if it has one. `size` is rounded up to a power
+    /// of two between 16 and 4096.
+    pub fn icon_url(&self, size: u16) -> Option<String> {
+        let icon = self.icon.as_deref().filter(|icon| !icon.is_empty())?;
+        Some(cdn::image_url("icons", self.id, icon, size))
+    }
 
 
-#[allow(non_snake_case)]
-#[derive(Deserialize, Debug)]
-struct JsonUser {
-    id: String,
-    username: String,
-    discriminator: String,
-    avatar: Option<String>,
-    defAvatar: String,
-    bio: Option<String>,
-    banner: Option<String>,
-    social: HashMap<String, String>,
-    color: Option<String>,
-    supporter: bool,
-    certifiedDev: bool,
-    r#mod: bool,
-    webMod: bool,
-    admin: bool,
-}
-
-#[derive(Debug)]
-pub struct User {
-    pub id: u64,
-    pub username: String,
-    pub discriminator: String,
-    pub avatar: Option<String>,
-    pub def_avatar: String,
-    pub bio: Option<String>,
-    pub banner: Option<String>,
-    pub youtube: Option<String>,
-    pub reddit: Option<String>,
-    pub twitter: Option<String>,
-    pub instagram: Option<String>,
-    pub github: Option<String>, 
-    pub color: Option<String>,
-    pub supporter: bool,
-    pub certified_dev: bool,
-    pub moderator: bool,
-    pub web_moderator: bool,
-    pub admin: bool,
-}
+    /// The URL of the server's page on top.gg.
+    pub fn page_url(&self) -> String {
+        format!("{}/servers/{}", TOPGG_URL, self.id)
+    }
 
 
-#[derive(Deserialize, Debug)]
-struct PartialJsonUser {
-    id: String,
-    username: String,
-    discriminator: String,
-    avatar: Option<String>
+    /// The URL users vote for the server on.
+    pub fn vote_url(&self) -> String {
+        format!("{}/vote", self.page_url())
+    }
 }
 
-#[derive(Debug)]
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct PartialUser {
-    pub id: u64,
+    pub id: Snowflake,
     pub username: String,
     pub discriminator: String,
     pub avatar: Option<String>
 }
 
 
-#[derive(Deserialize, Debug)]
-struct CheckVote {
-    voted: i8
-}
-
-
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct BotStats {
     pub server_count: Option<u32>,
     pub shards: Vec<u32>,
     pub shard_count: Option<u32>
 }
-
-
-#[derive(Serialize, Debug)]
-struct PostBotStats {
-    server_count: Option<u32>,
-    shards: Option<Vec<u32>>,
-    shard_id: Option<u32>,
-    shard_count: Option<u32>,
-}
\ No newline at end of file
@@ -0,0 +1,29 @@
+/// Emits a `tracing` event when the `tracing` feature is enabled, and does nothing otherwise, so
+/// logging doesn't need a `cfg` at every call site. Without the feature the fields are still used,
+/// inside a closure that's never called, so values only logged don't warn as unused.
+#[allow(unused_macros)]
+macro_rules! event {
+    (@unused $name:ident = % $value:expr, $($rest:tt)*) => {
+        let _ = || { let _ = &$value; };
+        event!(@unused $($rest)*);
+    };
+    (@unused $name:ident = ? $value:expr, $($rest:tt)*) => {
+        let _ = || { let _ = &$value; };
+        event!(@unused $($rest)*);
+    };
+    (@unused $name:ident = $value:expr, $($rest:tt)*) => {
+        let _ = || { let _ = &$value; };
+        event!(@unused $($rest)*);
+    };
+    (@unused $name:ident, $($rest:tt)*) => {
+        let _ = || { let _ = &$name; };
+        event!(@unused $($rest)*);
+    };
+    (@unused $message:literal) => {};
+    ($level:expr, $($fields:tt)*) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::event!($level, $($fields)*);
+        #[cfg(not(feature = "tracing"))]
+        event!(@unused $($fields)*);
+    };
+}
@@ -0,0 +1,110 @@
+//! Receiving webhooks in a [Rocket](https://rocket.rs) app. Needs the `rocket` feature.
+
+use std::sync::Arc;
+use ::rocket::fairing::{self, Fairing, Info, Kind};
+use ::rocket::http::Status;
+use ::rocket::outcome::Outcome;
+use ::rocket::request::{self, FromRequest, Request};
+use ::rocket::{Build, Rocket, State};
+
+use crate::webhook::events::{Broadcast, Events};
+use crate::webhook::{is_authorized, Event, Overflow, DEFAULT_QUEUE_CAPACITY};
+
+
+
+/// Returns a fairing which mounts the webhook route at `path`, and the stream of events it receives.
+/// The fairing manages `WebhookAuth` for you, so `TopggAuth` can be used on your own routes too.
+/// ## Examples
+/// ```
+/// let (webhook, events) = topgg::rocket::webhook("/topgg/webhook", auth);
+/// rocket::build()
+///     .attach(webhook)
+///     .launch()
+///     .await?;
+/// ```
+pub fn webhook(path: &str, auth: String) -> (WebhookFairing, Events<Event>) {
+    let broadcast = Arc::new(Broadcast::new(Some(DEFAULT_QUEUE_CAPACITY), Overflow::DropOldest));
+    let events = broadcast.subscribe();
+    let fairing = WebhookFairing {
+        path: path.to_string(),
+        auth,
+        broadcast,
+    };
+    (fairing, events)
+}
+
+
+/// Mounts the webhook route when Rocket ignites. See `webhook`.
+pub struct WebhookFairing {
+    path: String,
+    auth: String,
+    broadcast: Arc<Broadcast<Event>>,
+}
+
+#[::rocket::async_trait]
+impl Fairing for WebhookFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "top.gg webhook",
+            kind: Kind::Ignite,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: Rocket<Build>) -> fairing::Result {
+        Ok(rocket
+            .manage(WebhookAuth(self.auth.clone()))
+            .manage(WebhookState(self.broadcast.clone()))
+            .mount(self.path.as_str(), ::rocket::routes![receive]))
+    }
+}
+
+
+struct WebhookState(Arc<Broadcast<Event>>);
+
+
+#[::rocket::post("/", data = "<body>")]
+fn receive(_auth: TopggAuth, body: Vec<u8>, state: &State<WebhookState>) -> Status {
+    let event = match serde_json::from_slice::<Event>(&body) {
+        Ok(event) => event,
+        Err(_) => return Status::BadRequest,
+    };
+    match state.0.send(event) {
+        Ok(()) => Status::Ok,
+        Err(_) => Status::ServiceUnavailable,
+    }
+}
+
+
+/// The authorization string set on your top.gg webhook page, which `TopggAuth` checks requests against.
+/// Manage it yourself if you aren't using the `webhook` fairing.
+#[derive(Clone, Debug)]
+pub struct WebhookAuth(pub String);
+
+
+/// A request guard which only lets through requests with the right authorization header, failing
+/// with a 401 otherwise.
+/// ## Examples
+/// ```
+/// #[rocket::post("/vote", data = "<vote>")]
+/// fn vote(_auth: topgg::rocket::TopggAuth, vote: rocket::serde::json::Json<topgg::Event>) {
+///     println!("{:?}", vote);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TopggAuth;
+
+#[::rocket::async_trait]
+impl<'r> FromRequest<'r> for TopggAuth {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<TopggAuth, ()> {
+        let auth = match request.rocket().state::<WebhookAuth>() {
+            Some(auth) => auth,
+            None => return Outcome::Error((Status::InternalServerError, ())),
+        };
+        match request.headers().get_one("Authorization") {
+            Some(value) if is_authorized(&[&auth.0], Some(value)) => Outcome::Success(TopggAuth),
+            _ => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
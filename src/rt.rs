@@ -0,0 +1,24 @@
+//! Timers that work on any async runtime. tokio's are used when the `tokio` feature is enabled, since
+//! they're cheaper, and `futures-timer`'s otherwise.
+
+use std::time::{Duration, Instant};
+
+
+
+/// Waits for `duration`.
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(feature = "tokio")]
+    {
+        tokio::time::sleep(duration).await
+    }
+    #[cfg(not(feature = "tokio"))]
+    {
+        futures_timer::Delay::new(duration).await
+    }
+}
+
+
+/// Waits until `deadline`, returning straight away if it has passed.
+pub(crate) async fn sleep_until(deadline: Instant) {
+    sleep(deadline.saturating_duration_since(Instant::now())).await
+}
@@ -0,0 +1,113 @@
+//! Helpers for testing how your bot handles votes, by sending fake webhooks to your own server
+//! instead of clicking "Send Test" on top.gg. With the `client` feature there's also `MockTopgg`, a
+//! fake client for unit testing code that makes requests, and `FakeTopgg`, a fake top.gg server for
+//! testing the real client end to end. Needs the `testing` feature.
+//!
+//! ## Examples
+//! ```
+//! use topgg::testing::{send_test_vote, VoteBuilder};
+//!
+//! let (mut events, handle) = topgg::WebhookClient::start(0, auth.clone())?;
+//! let url = format!("http://{}/", handle.local_addr());
+//!
+//! let status = send_test_vote(&url, &auth, &VoteBuilder::bot(bot_id, user_id).weekend(true).build()).await?;
+//! assert_eq!(status, 200);
+//! ```
+
+use crate::webhook::{GuildWebhook, Vote, Webhook};
+use crate::Snowflake;
+
+#[cfg(feature = "client")]
+mod mock;
+#[cfg(feature = "client")]
+pub use mock::MockTopgg;
+#[cfg(feature = "client")]
+mod server;
+#[cfg(feature = "client")]
+pub use server::FakeTopgg;
+
+
+
+/// Builds the body of a fake webhook. Votes are built as real upvotes unless `test` is called.
+pub struct VoteBuilder {
+    vote: Vote,
+}
+impl VoteBuilder {
+    /// A vote by `user` for the bot `bot`.
+    pub fn bot(bot: impl Into<Snowflake>, user: impl Into<Snowflake>) -> VoteBuilder {
+        VoteBuilder {
+            vote: Vote::Bot(Webhook {
+                bot: bot.into(),
+                user: user.into(),
+                kind: "upvote".to_string(),
+                is_weekend: false,
+                query: None,
+            }),
+        }
+    }
+
+
+    /// A vote by `user` for the server `guild`.
+    pub fn guild(guild: impl Into<Snowflake>, user: impl Into<Snowflake>) -> VoteBuilder {
+        VoteBuilder {
+            vote: Vote::Guild(GuildWebhook {
+                guild: guild.into(),
+                user: user.into(),
+                kind: "upvote".to_string(),
+                query: None,
+            }),
+        }
+    }
+
+
+    /// Makes it a test webhook like the ones sent by "Send Test" on top.gg.
+    pub fn test(mut self) -> VoteBuilder {
+        match &mut self.vote {
+            Vote::Bot(vote) => vote.kind = "test".to_string(),
+            Vote::Guild(vote) => vote.kind = "test".to_string(),
+        }
+        self
+    }
+
+
+    /// Sets whether the vote was made on a weekend. Votes for servers don't have this so it's ignored for them.
+    pub fn weekend(mut self, is_weekend: bool) -> VoteBuilder {
+        if let Vote::Bot(vote) = &mut self.vote {
+            vote.is_weekend = is_weekend;
+        }
+        self
+    }
+
+
+    /// Sets the query string of the vote page, like `?ref=twitter`.
+    pub fn query(mut self, query: &str) -> VoteBuilder {
+        let query = Some(query.to_string());
+        match &mut self.vote {
+            Vote::Bot(vote) => vote.query = query,
+            Vote::Guild(vote) => vote.query = query,
+        }
+        self
+    }
+
+
+    pub fn build(self) -> Vote {
+        self.vote
+    }
+}
+
+
+/// Sends a webhook to `url` the way top.gg does, returning the status code your server responded with.
+///
+/// ## Arguments
+/// * `url` - The full URL your webhook is received on, such as `http://127.0.0.1:3030/topgg/webhook`
+/// * `auth` - The authorization string to send
+/// * `payload` - The vote to send, see `VoteBuilder`
+pub async fn send_test_vote(url: &str, auth: &str, payload: &Vote) -> Result<u16, reqwest::Error> {
+    let res = reqwest::Client::new()
+        .post(url)
+        .header("authorization", auth)
+        .json(payload)
+        .send()
+        .await?;
+    Ok(res.status().as_u16())
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use async_trait::async_trait;
+
+use crate::{Bot, BotStats, Snowflake, StatsUpdate, TopggApi, TopggError, User};
+
+
+
+/// A fake client which answers with the bots, users and votes it was given instead of asking top.gg,
+/// for unit testing code that takes a `TopggApi`. Anything it wasn't given is treated as not found,
+/// and users haven't voted unless a vote was added for them.
+/// ## Examples
+/// ```
+/// use topgg::testing::MockTopgg;
+/// use topgg::TopggApi;
+///
+/// let api = MockTopgg::new(bot_id).with_vote(bot_id, user_id);
+/// assert_eq!(api.voted_for_me(user_id.into()).await, Some(true));
+///
+/// api.set_voted(bot_id, user_id, false);
+/// assert_eq!(api.voted_for_me(user_id.into()).await, Some(false));
+/// ```
+pub struct MockTopgg {
+    bot_id: Snowflake,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    bots: HashMap<Snowflake, Bot>,
+    users: HashMap<Snowflake, User>,
+    votes: HashMap<Snowflake, Vec<Snowflake>>,
+    stats: HashMap<Snowflake, BotStats>,
+    is_weekend: bool,
+    post_error: Option<(u16, String)>,
+    posted: Vec<StatsUpdate>,
+}
+
+impl MockTopgg {
+    /// Returns a mock with nothing in it, acting as the client for `bot_id`.
+    pub fn new(bot_id: impl Into<Snowflake>) -> MockTopgg {
+        MockTopgg {
+            bot_id: bot_id.into(),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+
+    /// Answers requests for the bot with `bot`.
+    pub fn with_bot(self, bot: Bot) -> MockTopgg {
+        self.state.lock().unwrap().bots.insert(bot.id, bot);
+        self
+    }
+
+
+    /// Answers requests for the user with `user`.
+    pub fn with_user(self, user: User) -> MockTopgg {
+        self.state.lock().unwrap().users.insert(user.id, user);
+        self
+    }
+
+
+    /// Adds a vote by `user_id` for `bot_id`.
+    pub fn with_vote(self, bot_id: impl Into<Snowflake>, user_id: impl Into<Snowflake>) -> MockTopgg {
+        self.set_voted(bot_id, user_id, true);
+        self
+    }
+
+
+    /// Answers requests for the stats of `bot_id` with `stats`.
+    pub fn with_stats(self, bot_id: impl Into<Snowflake>, stats: BotStats) -> MockTopgg {
+        self.state.lock().unwrap().stats.insert(bot_id.into(), stats);
+        self
+    }
+
+
+    /// Sets whether it's the weekend, when votes count twice.
+    pub fn weekend(self, is_weekend: bool) -> MockTopgg {
+        self.state.lock().unwrap().is_weekend = is_weekend;
+        self
+    }
+
+
+    /// Makes posting stats fail as if top.gg responded with `status`.
+    pub fn fail_posts(self, status: u16, message: &str) -> MockTopgg {
+        self.state.lock().unwrap().post_error = Some((status, message.to_string()));
+        self
+    }
+
+
+    /// Adds or removes a vote by `user_id` for `bot_id`, such as to have a vote run out part way
+    /// through a test.
+    pub fn set_voted(&self, bot_id: impl Into<Snowflake>, user_id: impl Into<Snowflake>, voted: bool) {
+        let user_id = user_id.into();
+        let mut state = self.state.lock().unwrap();
+        let votes = state.votes.entry(bot_id.into()).or_default();
+        votes.retain(|voter| *voter != user_id);
+        if voted {
+            votes.push(user_id);
+        }
+    }
+
+
+    /// Every stats update posted so far, oldest first. Failed posts aren't included.
+    pub fn posted_stats(&self) -> Vec<StatsUpdate> {
+        self.state.lock().unwrap().posted.clone()
+    }
+}
+
+
+#[async_trait]
+impl TopggApi for MockTopgg {
+    fn bot_id(&self) -> Snowflake {
+        self.bot_id
+    }
+
+    async fn bot(&self, bot_id: Snowflake) -> Option<Bot> {
+        self.state.lock().unwrap().bots.get(&bot_id).cloned()
+    }
+
+    async fn user(&self, user_id: Snowflake) -> Option<User> {
+        self.state.lock().unwrap().users.get(&user_id).cloned()
+    }
+
+    async fn votes(&self, bot_id: Snowflake) -> Option<Vec<Snowflake>> {
+        Some(self.state.lock().unwrap().votes.get(&bot_id).cloned().unwrap_or_default())
+    }
+
+    async fn voted(&self, bot_id: Snowflake, user_id: Snowflake) -> Option<bool> {
+        let state = self.state.lock().unwrap();
+        Some(state.votes.get(&bot_id).map_or(false, |votes| votes.contains(&user_id)))
+    }
+
+    async fn is_weekend(&self) -> Option<bool> {
+        Some(self.state.lock().unwrap().is_weekend)
+    }
+
+    async fn get_bot_stats(&self, bot_id: Snowflake) -> Option<BotStats> {
+        self.state.lock().unwrap().stats.get(&bot_id).cloned()
+    }
+
+    async fn post_bot_stats(&self, stats: StatsUpdate) -> Result<(), TopggError> {
+        stats.validate()?;
+        let mut state = self.state.lock().unwrap();
+        if let Some((status, message)) = &state.post_error {
+            return Err(TopggError::Api {
+                status: *status,
+                message: message.clone(),
+            });
+        }
+        state.posted.push(stats);
+        Ok(())
+    }
+}
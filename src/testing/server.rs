@@ -0,0 +1,264 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use futures::channel::oneshot;
+use serde_json::{json, Value};
+use warp::http::{Method, StatusCode};
+use warp::path::FullPath;
+use warp::Filter;
+
+use crate::client::{JsonBot, JsonUser};
+use crate::{Bot, BotStats, Snowflake, User};
+
+
+
+/// A fake top.gg running on a local port, answering the bots, users, votes, check, stats and weekend
+/// endpoints from fixtures instead of top.gg's data, so your integration can be tested end to end
+/// without a token or a network. Point a client at it with `Topgg::base_url`. The server stops when
+/// this is dropped.
+/// ## Examples
+/// ```
+/// use topgg::testing::FakeTopgg;
+///
+/// let fake = FakeTopgg::start()?;
+/// fake.add_vote(bot_id, user_id);
+///
+/// let client = topgg::Topgg::new(bot_id, "token".to_string()).base_url(fake.url());
+/// assert_eq!(client.voted_for_me(user_id).await, Some(true));
+///
+/// fake.fail_next(1, 503);
+/// assert_eq!(client.voted_for_me(user_id).await, None);
+/// ```
+pub struct FakeTopgg {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    _shutdown: oneshot::Sender<()>,
+}
+
+#[derive(Default)]
+struct State {
+    bots: HashMap<Snowflake, Bot>,
+    users: HashMap<Snowflake, User>,
+    votes: HashMap<Snowflake, Vec<Snowflake>>,
+    stats: HashMap<Snowflake, Value>,
+    is_weekend: bool,
+    token: Option<String>,
+    failures: VecDeque<u16>,
+    posted: Vec<(Snowflake, Value)>,
+}
+
+impl FakeTopgg {
+    /// Starts the server on a free port of `127.0.0.1`. Must be called within a tokio runtime.
+    pub fn start() -> Result<FakeTopgg, warp::Error> {
+        let state = Arc::new(Mutex::new(State::default()));
+        let routes = {
+            let state = state.clone();
+            warp::method()
+                .and(warp::path::full())
+                .and(warp::query::<HashMap<String, String>>())
+                .and(warp::header::optional::<String>("authorization"))
+                .and(warp::body::bytes())
+                .map(move |method: Method, path: FullPath, query: HashMap<String, String>, auth: Option<String>, body: warp::hyper::body::Bytes| {
+                    let (status, body) = respond(&mut state.lock().unwrap(), method, path.as_str(), &query, auth, &body);
+                    warp::reply::with_status(warp::reply::json(&body), status)
+                })
+        };
+
+        let (shutdown_send, shutdown_read) = oneshot::channel::<()>();
+        let signal = async move {
+            // Dropping the sender along with the fake stops the server too.
+            let _ = shutdown_read.await;
+        };
+        let (addr, server) = warp::serve(routes).try_bind_with_graceful_shutdown(([127, 0, 0, 1], 0), signal)?;
+        tokio::task::spawn(server);
+
+        Ok(FakeTopgg {
+            addr,
+            state,
+            _shutdown: shutdown_send,
+        })
+    }
+
+
+    /// The address to give `Topgg::base_url`, like `http://127.0.0.1:49152/api`.
+    pub fn url(&self) -> String {
+        format!("http://{}/api", self.addr)
+    }
+
+
+    /// Answers requests for the bot with `bot`.
+    pub fn add_bot(&self, bot: Bot) {
+        self.state.lock().unwrap().bots.insert(bot.id, bot);
+    }
+
+
+    /// Answers requests for the user with `user`, and uses their name in the votes they're listed in.
+    pub fn add_user(&self, user: User) {
+        self.state.lock().unwrap().users.insert(user.id, user);
+    }
+
+
+    /// Adds a vote by `user_id` for `bot_id`.
+    pub fn add_vote(&self, bot_id: impl Into<Snowflake>, user_id: impl Into<Snowflake>) {
+        let user_id = user_id.into();
+        let mut state = self.state.lock().unwrap();
+        let votes = state.votes.entry(bot_id.into()).or_default();
+        if !votes.contains(&user_id) {
+            votes.push(user_id);
+        }
+    }
+
+
+    /// Removes the vote by `user_id` for `bot_id`, as if it ran out.
+    pub fn remove_vote(&self, bot_id: impl Into<Snowflake>, user_id: impl Into<Snowflake>) {
+        let user_id = user_id.into();
+        if let Some(votes) = self.state.lock().unwrap().votes.get_mut(&bot_id.into()) {
+            votes.retain(|voter| *voter != user_id);
+        }
+    }
+
+
+    /// Answers requests for the stats of `bot_id` with `stats`. Stats posted for a bot replace these.
+    pub fn set_stats(&self, bot_id: impl Into<Snowflake>, stats: BotStats) {
+        let stats = serde_json::to_value(stats).unwrap();
+        self.state.lock().unwrap().stats.insert(bot_id.into(), stats);
+    }
+
+
+    /// Sets whether it's the weekend, when votes count twice.
+    pub fn set_weekend(&self, is_weekend: bool) {
+        self.state.lock().unwrap().is_weekend = is_weekend;
+    }
+
+
+    /// Answers requests with a 401 unless they're made with `token`. Any token is accepted otherwise.
+    pub fn require_token(&self, token: &str) {
+        self.state.lock().unwrap().token = Some(token.to_string());
+    }
+
+
+    /// Answers the next `count` requests with `status` and an error message instead of the fixtures,
+    /// for testing how failures and retries are handled.
+    pub fn fail_next(&self, count: usize, status: u16) {
+        let mut state = self.state.lock().unwrap();
+        state.failures.extend(std::iter::repeat(status).take(count));
+    }
+
+
+    /// The bodies of every successful stats post so far, oldest first, along with the bot they were for.
+    pub fn posted_stats(&self) -> Vec<(Snowflake, Value)> {
+        self.state.lock().unwrap().posted.clone()
+    }
+}
+
+
+fn respond(
+    state: &mut State,
+    method: Method,
+    path: &str,
+    query: &HashMap<String, String>,
+    auth: Option<String>,
+    body: &[u8],
+) -> (StatusCode, Value) {
+    if let Some(status) = state.failures.pop_front() {
+        let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        return error(status, "induced failure");
+    }
+    let authorized = match &state.token {
+        Some(token) => auth.as_deref() == Some(token.as_str()),
+        None => auth.map_or(false, |auth| !auth.is_empty()),
+    };
+    if !authorized {
+        return error(StatusCode::UNAUTHORIZED, "Unauthorized");
+    }
+
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match (method, segments.as_slice()) {
+        (Method::GET, ["api", "weekend"]) => (StatusCode::OK, json!({ "is_weekend": state.is_weekend })),
+        (Method::GET, ["api", "bots", bot_id]) => match id(bot_id).and_then(|bot_id| state.bots.get(&bot_id)) {
+            Some(bot) => (StatusCode::OK, json(JsonBot::from(bot.clone()))),
+            None => not_found(),
+        },
+        (Method::GET, ["api", "users", user_id]) => match id(user_id).and_then(|user_id| state.users.get(&user_id)) {
+            Some(user) => (StatusCode::OK, json(JsonUser::from(user.clone()))),
+            None => not_found(),
+        },
+        (Method::GET, ["api", "bots", bot_id, "votes"]) => {
+            let votes = id(bot_id).and_then(|bot_id| state.votes.get(&bot_id)).cloned().unwrap_or_default();
+            let voters: Vec<Value> = votes.iter().map(|user_id| partial_user(state, *user_id)).collect();
+            (StatusCode::OK, Value::Array(voters))
+        }
+        (Method::GET, ["api", "bots", bot_id, "check"]) => {
+            let user_id = match query.get("userId").and_then(|user_id| user_id.parse().ok()) {
+                Some(user_id) => user_id,
+                None => return error(StatusCode::BAD_REQUEST, "userId is required"),
+            };
+            let voted = id(bot_id)
+                .and_then(|bot_id| state.votes.get(&bot_id))
+                .map_or(false, |votes| votes.contains(&user_id));
+            (StatusCode::OK, json!({ "voted": voted as u8 }))
+        }
+        (Method::GET, ["api", "bots", bot_id, "stats"]) => match id(bot_id).and_then(|bot_id| state.stats.get(&bot_id)) {
+            Some(stats) => (StatusCode::OK, stats.clone()),
+            None => not_found(),
+        },
+        (Method::POST, ["api", "bots", bot_id, "stats"]) => {
+            let bot_id = match id(bot_id) {
+                Some(bot_id) => bot_id,
+                None => return not_found(),
+            };
+            let posted: Value = match serde_json::from_slice(body) {
+                Ok(posted) => posted,
+                Err(_) => return error(StatusCode::BAD_REQUEST, "Invalid body"),
+            };
+            let stats = json!({
+                "server_count": posted.get("server_count"),
+                "shards": posted.get("shards").cloned().unwrap_or_else(|| json!([])),
+                "shard_count": posted.get("shard_count"),
+            });
+            state.stats.insert(bot_id, stats);
+            state.posted.push((bot_id, posted));
+            (StatusCode::OK, Value::Null)
+        }
+        _ => not_found(),
+    }
+}
+
+
+fn id(segment: &str) -> Option<Snowflake> {
+    segment.parse().ok()
+}
+
+
+fn json<T: serde::Serialize>(value: T) -> Value {
+    serde_json::to_value(value).unwrap()
+}
+
+
+/// How a voter is listed in the votes of a bot, using the user's fixture if there is one.
+fn partial_user(state: &State, user_id: Snowflake) -> Value {
+    match state.users.get(&user_id) {
+        Some(user) => json!({
+            "id": user.id,
+            "username": user.username,
+            "discriminator": user.discriminator,
+            "avatar": user.avatar,
+        }),
+        None => json!({
+            "id": user_id,
+            "username": "",
+            "discriminator": "0",
+            "avatar": null,
+        }),
+    }
+}
+
+
+fn not_found() -> (StatusCode, Value) {
+    error(StatusCode::NOT_FOUND, "Not found")
+}
+
+
+fn error(status: StatusCode, message: &str) -> (StatusCode, Value) {
+    (status, json!({ "error": message }))
+}
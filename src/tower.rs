@@ -0,0 +1,131 @@
+//! Receiving webhooks with a plain `tower::Service`, which can be mounted in hyper or any other HTTP
+//! stack built on tower without needing warp. Needs the `tower` feature.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use futures::future::BoxFuture;
+use hyper::body::{Buf, HttpBody};
+use hyper::http::{header, Method, Request, Response, StatusCode};
+use hyper::Body;
+use tower_service::Service;
+
+use crate::webhook::events::{Broadcast, Events};
+use crate::webhook::{self, is_authorized, Event, Overflow, DEFAULT_QUEUE_CAPACITY};
+
+
+
+/// The largest body read, the same as the warp server's default and much bigger than any webhook
+/// top.gg sends.
+const BODY_LIMIT: u64 = 16 * 1024;
+
+
+/// Returns a service which receives webhooks, and the stream of events it receives.
+/// ## Examples
+/// ```
+/// use hyper::service::make_service_fn;
+/// use std::convert::Infallible;
+///
+/// let (service, events) = topgg::tower::service(auth);
+/// let make_service = make_service_fn(move |_| {
+///     let service = service.clone();
+///     async move { Ok::<_, Infallible>(service) }
+/// });
+/// hyper::Server::bind(&([0, 0, 0, 0], 3030).into())
+///     .serve(make_service)
+///     .await?;
+/// ```
+pub fn service(auth: String) -> (WebhookService, Events<Event>) {
+    let broadcast = Arc::new(Broadcast::new(Some(DEFAULT_QUEUE_CAPACITY), Overflow::DropOldest));
+    let events = broadcast.subscribe();
+    let service = WebhookService {
+        auth: auth.into(),
+        broadcast,
+    };
+    (service, events)
+}
+
+
+/// A service which checks the authorization of webhook requests and parses them. See `service`.
+#[derive(Clone)]
+pub struct WebhookService {
+    auth: Arc<str>,
+    broadcast: Arc<Broadcast<Event>>,
+}
+impl WebhookService {
+    async fn handle<B: HttpBody>(&self, req: Request<B>) -> StatusCode {
+        if req.method() != Method::POST {
+            return StatusCode::METHOD_NOT_ALLOWED;
+        }
+
+        let authorization = req.headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+        // Checked before reading the body, so unauthorized clients can't make the service read anything.
+        if !is_authorized(&[&*self.auth], authorization) {
+            return StatusCode::UNAUTHORIZED;
+        }
+        let body = match read_limited(req.into_body()).await {
+            Ok(body) => body,
+            Err(status) => return status,
+        };
+
+        let event = match webhook::parse_body(&body) {
+            Ok(event) => event,
+            Err(e) => return StatusCode::from_u16(e.status()).unwrap_or(StatusCode::BAD_REQUEST),
+        };
+        match self.broadcast.send(event) {
+            Ok(()) => StatusCode::OK,
+            Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+/// Reads the body, failing with a 413 once it's over `BODY_LIMIT` rather than reading the rest.
+async fn read_limited<B: HttpBody>(body: B) -> Result<Vec<u8>, StatusCode> {
+    let hint = body.size_hint();
+    if hint.lower() > BODY_LIMIT || matches!(hint.upper(), Some(upper) if upper > BODY_LIMIT) {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    futures::pin_mut!(body);
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let mut chunk = chunk.map_err(|_| StatusCode::BAD_REQUEST)?;
+        if (bytes.len() + chunk.remaining()) as u64 > BODY_LIMIT {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        while chunk.has_remaining() {
+            let part = chunk.chunk();
+            let len = part.len();
+            bytes.extend_from_slice(part);
+            chunk.advance(len);
+        }
+    }
+    Ok(bytes)
+}
+
+
+impl<B> Service<Request<B>> for WebhookService
+where
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+{
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response<Body>, Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let service = self.clone();
+        Box::pin(async move {
+            let status = service.handle(req).await;
+            let mut res = Response::new(Body::empty());
+            *res.status_mut() = status;
+            Ok(res)
+        })
+    }
+}
@@ -0,0 +1,406 @@
+//! Keeping track of who has voted recently, since a vote on top.gg only counts for 12 hours, and
+//! reminding them when they can vote again. Needs the `votes` feature.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use async_trait::async_trait;
+
+use crate::webhook::events::{Broadcast, Events};
+use crate::webhook::{Event, Overflow, DEFAULT_QUEUE_CAPACITY};
+use crate::Snowflake;
+
+mod analytics;
+pub use analytics::{RepeatVoter, VoteAnalytics, VoteBucket, WeekendShare};
+mod export;
+mod reminders;
+pub use reminders::VoteReminders;
+#[cfg(feature = "redis")]
+mod redis;
+#[cfg(feature = "redis")]
+pub use self::redis::RedisStore;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+
+
+/// How long a vote on top.gg lasts before the user can vote again.
+pub const VOTE_DURATION: Duration = Duration::from_secs(12 * 60 * 60);
+
+
+/// Remembers when users voted so whether they have an active vote can be answered without asking
+/// top.gg. Votes are fed in from the webhook with `ingest` or from `Topgg::voted` with `record_voted`,
+/// and are forgotten once they're older than 12 hours.
+///
+/// Votes are kept in memory unless another `VoteStore` is given with `with_store`. When a vote runs
+/// out a `VoteExpired` event is sent to the streams from `expirations`.
+/// ## Examples
+/// ```
+/// use futures::StreamExt;
+/// use topgg::votes::VoteManager;
+///
+/// let votes = VoteManager::new();
+/// let (mut events, _handle) = topgg::WebhookClient::start(3030, auth)?;
+/// while let Some(event) = events.next().await {
+///     votes.ingest(&event).await?;
+/// }
+///
+/// // elsewhere, when a command is used
+/// if votes.has_active_vote(user_id).await? {
+///     // ...
+/// }
+/// ```
+pub struct VoteManager<S = MemoryStore> {
+    duration: Duration,
+    store: S,
+    expired: Broadcast<VoteExpired>,
+}
+impl VoteManager<MemoryStore> {
+    /// Returns a new manager which keeps votes in memory.
+    pub fn new() -> VoteManager<MemoryStore> {
+        VoteManager::with_store(MemoryStore::new())
+    }
+}
+impl<S: VoteStore> VoteManager<S> {
+    /// Returns a new manager which keeps votes in the given store.
+    /// ## Examples
+    /// ```
+    /// let votes = topgg::votes::VoteManager::with_store(my_store);
+    /// ```
+    pub fn with_store(store: S) -> VoteManager<S> {
+        VoteManager {
+            duration: VOTE_DURATION,
+            store,
+            expired: Broadcast::new(Some(DEFAULT_QUEUE_CAPACITY), Overflow::DropOldest),
+        }
+    }
+
+
+    /// Sets how long votes last, instead of 12 hours.
+    pub fn with_duration(mut self, duration: Duration) -> VoteManager<S> {
+        self.duration = duration;
+        self
+    }
+
+
+    /// The store the votes are kept in.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+
+    /// Records the vote from a webhook as made now. Test webhooks are ignored.
+    pub async fn ingest(&self, event: &Event) -> Result<(), VoteStoreError> {
+        match event {
+            Event::BotVote(_) | Event::GuildVote(_) => self.record(event.user()).await,
+            Event::Test(_) => Ok(()),
+        }
+    }
+
+
+    /// Records that the user voted just now.
+    pub async fn record(&self, user_id: impl Into<Snowflake>) -> Result<(), VoteStoreError> {
+        self.record_at(user_id, SystemTime::now()).await
+    }
+
+
+    /// Records that the user voted at the given time, such as when loading votes saved elsewhere.
+    pub async fn record_at(&self, user_id: impl Into<Snowflake>, at: SystemTime) -> Result<(), VoteStoreError> {
+        self.store.record(user_id.into(), at).await
+    }
+
+
+    /// Counts the votes in the store's history, keeping the `top` users who voted most. Stores which
+    /// only keep each user's last vote, like the default `MemoryStore`, count that vote alone; see
+    /// `MemoryStore::keep_history`.
+    pub async fn analytics(&self, top: usize) -> Result<VoteAnalytics, VoteStoreError> {
+        Ok(VoteAnalytics::from_votes(self.store.history().await?, top))
+    }
+
+
+    /// Writes the store's history of votes to a CSV file at `path`, with the columns `user_id`,
+    /// `voted_at` (in UTC, like `2024-03-01T18:26:14Z`) and `voted_at_unix`, for pulling voters into a
+    /// spreadsheet for a giveaway. The file is replaced if it exists.
+    /// ## Examples
+    /// ```
+    /// votes.export_csv("voters.csv").await?;
+    /// ```
+    pub async fn export_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let votes = self.store.history().await.map_err(io::Error::other)?;
+        export::write_csv(path.as_ref(), &votes)
+    }
+
+
+    /// Writes the store's history of votes to a JSON file at `path`, as an array of objects with the
+    /// same fields as the columns of `export_csv`. The file is replaced if it exists.
+    pub async fn export_json(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let votes = self.store.history().await.map_err(io::Error::other)?;
+        export::write_json(path.as_ref(), &votes)
+    }
+
+
+    /// Records the result of `Topgg::voted`. top.gg doesn't say when the vote was made, so a vote that
+    /// isn't tracked yet is treated as made now, while one that's already tracked keeps its time. If
+    /// top.gg says the user hasn't voted, a tracked vote is forgotten and `VoteExpired` is sent for it.
+    /// ## Examples
+    /// ```
+    /// if let Some(voted) = client.voted_for_me(user_id).await {
+    ///     votes.record_voted(user_id, voted).await?;
+    /// }
+    /// ```
+    pub async fn record_voted(&self, user_id: impl Into<Snowflake>, voted: bool) -> Result<(), VoteStoreError> {
+        let user_id = user_id.into();
+        if !voted {
+            if let Some(voted_at) = self.store.get(user_id).await? {
+                self.expire(user_id, voted_at).await?;
+            }
+            return Ok(());
+        }
+        if self.expires_at(user_id).await?.is_none() {
+            self.record(user_id).await?;
+        }
+        Ok(())
+    }
+
+
+    /// Whether the user voted within the last 12 hours.
+    pub async fn has_active_vote(&self, user_id: impl Into<Snowflake>) -> Result<bool, VoteStoreError> {
+        Ok(self.expires_at(user_id).await?.is_some())
+    }
+
+
+    /// When the user's vote runs out and they can vote again, or `None` if they have no active vote.
+    pub async fn expires_at(&self, user_id: impl Into<Snowflake>) -> Result<Option<SystemTime>, VoteStoreError> {
+        let user_id = user_id.into();
+        let voted_at = match self.store.get(user_id).await? {
+            Some(voted_at) => voted_at,
+            None => return Ok(None),
+        };
+        let expires_at = voted_at + self.duration;
+        if expires_at <= SystemTime::now() {
+            self.expire(user_id, voted_at).await?;
+            return Ok(None);
+        }
+        Ok(Some(expires_at))
+    }
+
+
+    /// Forgets every vote that has run out. Expired votes are also forgotten when they're looked up,
+    /// so without `watch_expirations` running this only needs calling now and then to free the space
+    /// used by users who don't come back.
+    pub async fn prune(&self) -> Result<(), VoteStoreError> {
+        let now = SystemTime::now();
+        for (user_id, voted_at) in self.store.iter().await? {
+            if voted_at + self.duration <= now {
+                self.expire(user_id, voted_at).await?;
+            }
+        }
+        Ok(())
+    }
+
+
+    /// Returns a stream of the votes that run out from now on, for revoking voter only roles or
+    /// reminding users they can vote again. Votes are only noticed to have run out when they're looked
+    /// up or pruned, so `watch_expirations` should be running for the events to arrive on time.
+    /// ## Examples
+    /// ```
+    /// use futures::StreamExt;
+    ///
+    /// let mut expirations = votes.expirations();
+    /// while let Some(expired) = expirations.next().await {
+    ///     remove_voter_role(expired.user_id).await;
+    /// }
+    /// ```
+    pub fn expirations(&self) -> Events<VoteExpired> {
+        self.expired.subscribe()
+    }
+
+
+    /// Prunes the votes every `interval` forever, so each `VoteExpired` event is sent at most
+    /// `interval` after the vote ran out. If the store fails the error is returned and checking stops.
+    /// ## Examples
+    /// ```
+    /// let votes = std::sync::Arc::new(topgg::votes::VoteManager::new());
+    /// let watcher = votes.clone();
+    /// tokio::spawn(async move { watcher.watch_expirations(Duration::from_secs(60)).await });
+    /// ```
+    pub async fn watch_expirations(&self, interval: Duration) -> Result<(), VoteStoreError> {
+        loop {
+            self.prune().await?;
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+
+    /// Forgets the vote made at `voted_at`, sending `VoteExpired` if this call is what removed it. A
+    /// vote made again since it was read is kept, and the event is sent once however many callers
+    /// notice the same vote ran out.
+    async fn expire(&self, user_id: Snowflake, voted_at: SystemTime) -> Result<(), VoteStoreError> {
+        if self.store.expire_if(user_id, voted_at).await? {
+            // Having no subscribers isn't a problem.
+            let _ = self.expired.send(VoteExpired { user_id, voted_at });
+        }
+        Ok(())
+    }
+}
+impl Default for VoteManager<MemoryStore> {
+    fn default() -> VoteManager<MemoryStore> {
+        VoteManager::new()
+    }
+}
+
+
+/// A vote which ran out, from `VoteManager::expirations`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoteExpired {
+    /// The user who can vote again.
+    pub user_id: Snowflake,
+    /// When they voted.
+    pub voted_at: SystemTime,
+}
+
+
+/// Where a `VoteManager` keeps the time each user last voted. Implement this to keep votes in
+/// your own database.
+///
+/// ## Correctness
+/// Several callers can notice the same vote ran out at once, and the user can vote again in the
+/// meantime. `expire_if` has to check and delete in one step, such as with a conditional `DELETE`
+/// or a transaction, or a vote made again can be lost and `VoteExpired` sent twice.
+#[async_trait]
+pub trait VoteStore: Send + Sync {
+    /// Saves that the user voted at the given time, replacing any vote saved before.
+    async fn record(&self, user_id: Snowflake, at: SystemTime) -> Result<(), VoteStoreError>;
+
+    /// The time the user last voted, if a vote is saved for them.
+    async fn get(&self, user_id: Snowflake) -> Result<Option<SystemTime>, VoteStoreError>;
+
+    /// Forgets the user's vote. Doing so when no vote is saved for them isn't an error.
+    async fn expire(&self, user_id: Snowflake) -> Result<(), VoteStoreError>;
+
+    /// Forgets the user's vote only if it's still the one made at `voted_at`, returning whether it was
+    /// forgotten. This has to be done in one step, see the note on `VoteStore`.
+    async fn expire_if(&self, user_id: Snowflake, voted_at: SystemTime) -> Result<bool, VoteStoreError>;
+
+    /// Every user with a saved vote along with when they voted.
+    async fn iter(&self) -> Result<Vec<(Snowflake, SystemTime)>, VoteStoreError>;
+
+    /// Every vote saved, oldest first, including the earlier votes of users who voted again and votes
+    /// which ran out, for `VoteManager::analytics`. By default this is only each user's last vote, from
+    /// `iter`.
+    async fn history(&self) -> Result<Vec<(Snowflake, SystemTime)>, VoteStoreError> {
+        let mut votes = self.iter().await?;
+        votes.sort_by_key(|(_, at)| *at);
+        Ok(votes)
+    }
+}
+
+
+/// Keeps votes in memory, so they're lost when the process stops.
+#[derive(Default)]
+pub struct MemoryStore {
+    votes: Mutex<HashMap<Snowflake, SystemTime>>,
+    history: Option<Mutex<Vec<(Snowflake, SystemTime)>>>,
+    history_age: Duration,
+}
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+
+    /// Also keeps every vote made within the last `max_age` for `VoteManager::analytics`, instead of
+    /// only each user's last one.
+    /// ## Examples
+    /// ```
+    /// let store = MemoryStore::new().keep_history(Duration::from_secs(30 * 24 * 60 * 60));
+    /// let votes = VoteManager::with_store(store);
+    /// ```
+    pub fn keep_history(mut self, max_age: Duration) -> MemoryStore {
+        self.history = Some(Mutex::new(Vec::new()));
+        self.history_age = max_age;
+        self
+    }
+}
+#[async_trait]
+impl VoteStore for MemoryStore {
+    async fn record(&self, user_id: Snowflake, at: SystemTime) -> Result<(), VoteStoreError> {
+        self.votes.lock().unwrap().insert(user_id, at);
+        if let Some(history) = &self.history {
+            let mut history = history.lock().unwrap();
+            let oldest = SystemTime::now().checked_sub(self.history_age);
+            history.retain(|(_, voted_at)| Some(*voted_at) >= oldest);
+            let index = history.partition_point(|(_, voted_at)| *voted_at <= at);
+            history.insert(index, (user_id, at));
+        }
+        Ok(())
+    }
+
+
+    async fn get(&self, user_id: Snowflake) -> Result<Option<SystemTime>, VoteStoreError> {
+        Ok(self.votes.lock().unwrap().get(&user_id).copied())
+    }
+
+
+    async fn expire(&self, user_id: Snowflake) -> Result<(), VoteStoreError> {
+        self.votes.lock().unwrap().remove(&user_id);
+        Ok(())
+    }
+
+
+    async fn expire_if(&self, user_id: Snowflake, voted_at: SystemTime) -> Result<bool, VoteStoreError> {
+        let mut votes = self.votes.lock().unwrap();
+        if votes.get(&user_id) != Some(&voted_at) {
+            return Ok(false);
+        }
+        votes.remove(&user_id);
+        Ok(true)
+    }
+
+
+    async fn iter(&self) -> Result<Vec<(Snowflake, SystemTime)>, VoteStoreError> {
+        Ok(self.votes.lock().unwrap().iter().map(|(user_id, at)| (*user_id, *at)).collect())
+    }
+
+
+    async fn history(&self) -> Result<Vec<(Snowflake, SystemTime)>, VoteStoreError> {
+        match &self.history {
+            Some(history) => Ok(history.lock().unwrap().clone()),
+            None => {
+                let mut votes = self.iter().await?;
+                votes.sort_by_key(|(_, at)| *at);
+                Ok(votes)
+            }
+        }
+    }
+}
+
+
+/// An error from the storage behind a `VoteStore`, such as a lost database connection.
+#[derive(Debug)]
+pub struct VoteStoreError {
+    source: Box<dyn Error + Send + Sync>,
+}
+impl VoteStoreError {
+    /// Wraps an error from a store's backend.
+    pub fn new(source: impl Into<Box<dyn Error + Send + Sync>>) -> VoteStoreError {
+        VoteStoreError {
+            source: source.into(),
+        }
+    }
+}
+impl std::fmt::Display for VoteStoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "vote store failed: {}", self.source)
+    }
+}
+impl Error for VoteStoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.source)
+    }
+}
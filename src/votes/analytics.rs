@@ -0,0 +1,133 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::Snowflake;
+
+
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+
+/// Counts of a bot's votes for rendering on a dashboard, from `VoteManager::analytics` or built from
+/// any list of votes with `from_votes`. Times are bucketed in UTC.
+/// ## Examples
+/// ```
+/// let analytics = votes.analytics(10).await?;
+/// println!("{} votes from {} users", analytics.total, analytics.unique_voters);
+/// for day in &analytics.per_day {
+///     println!("{:?}: {}", day.start, day.votes);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoteAnalytics {
+    /// How many votes there were.
+    pub total: usize,
+    /// How many different users voted.
+    pub unique_voters: usize,
+    /// The votes in each hour that had any, oldest first.
+    pub per_hour: Vec<VoteBucket>,
+    /// The votes on each day that had any, oldest first.
+    pub per_day: Vec<VoteBucket>,
+    /// How the votes split between weekends, when top.gg counts votes twice, and weekdays.
+    pub weekend: WeekendShare,
+    /// The users who voted the most, most votes first.
+    pub top_voters: Vec<RepeatVoter>,
+}
+impl VoteAnalytics {
+    /// Counts `votes`, given as the user who voted and when, keeping the `top` users who voted most.
+    pub fn from_votes(votes: impl IntoIterator<Item = (Snowflake, SystemTime)>, top: usize) -> VoteAnalytics {
+        let mut total = 0;
+        let mut hours = BTreeMap::<u64, usize>::new();
+        let mut days = BTreeMap::<u64, usize>::new();
+        let mut weekend = WeekendShare::default();
+        let mut voters = HashMap::<Snowflake, RepeatVoter>::new();
+        for (user_id, at) in votes {
+            total += 1;
+            let secs = at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            *hours.entry(secs - secs % HOUR).or_default() += 1;
+            *days.entry(secs - secs % DAY).or_default() += 1;
+            if is_weekend(secs) {
+                weekend.weekend += 1;
+            } else {
+                weekend.weekday += 1;
+            }
+            let voter = voters.entry(user_id).or_insert(RepeatVoter {
+                user_id,
+                votes: 0,
+                last_voted: at,
+            });
+            voter.votes += 1;
+            voter.last_voted = voter.last_voted.max(at);
+        }
+
+        let unique_voters = voters.len();
+        let mut top_voters = voters.into_values().collect::<Vec<_>>();
+        top_voters.sort_by(|a, b| b.votes.cmp(&a.votes).then(b.last_voted.cmp(&a.last_voted)));
+        top_voters.truncate(top);
+        VoteAnalytics {
+            total,
+            unique_voters,
+            per_hour: buckets(hours),
+            per_day: buckets(days),
+            weekend,
+            top_voters,
+        }
+    }
+}
+
+
+/// The votes within an hour or a day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VoteBucket {
+    /// When the hour or day started.
+    pub start: SystemTime,
+    pub votes: usize,
+}
+
+
+/// How many votes were made on weekends, Friday to Sunday in UTC like top.gg's, and on weekdays.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WeekendShare {
+    pub weekend: usize,
+    pub weekday: usize,
+}
+impl WeekendShare {
+    /// The fraction of the votes made on weekends, from 0 to 1, or 0 if there were no votes.
+    pub fn weekend_fraction(&self) -> f64 {
+        let total = self.weekend + self.weekday;
+        if total == 0 {
+            return 0.0;
+        }
+        self.weekend as f64 / total as f64
+    }
+}
+
+
+/// A user and how many times they voted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepeatVoter {
+    pub user_id: Snowflake,
+    pub votes: usize,
+    /// When they last voted.
+    pub last_voted: SystemTime,
+}
+
+
+fn buckets(counts: BTreeMap<u64, usize>) -> Vec<VoteBucket> {
+    counts
+        .into_iter()
+        .map(|(start, votes)| VoteBucket {
+            start: UNIX_EPOCH + Duration::from_secs(start),
+            votes,
+        })
+        .collect()
+}
+
+
+/// Whether the time, in seconds since the epoch, falls on a Friday, Saturday or Sunday in UTC.
+fn is_weekend(secs: u64) -> bool {
+    // The epoch was a Thursday, so this counts from Monday as 0.
+    let weekday = (secs / DAY + 3) % 7;
+    weekday >= 4
+}
@@ -0,0 +1,74 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::Serialize;
+
+use crate::Snowflake;
+
+
+
+#[derive(Serialize)]
+struct ExportedVote {
+    /// A string, since spreadsheets and JavaScript lose precision on IDs as numbers.
+    user_id: String,
+    voted_at: String,
+    voted_at_unix: u64,
+}
+impl ExportedVote {
+    fn new(user_id: Snowflake, at: SystemTime) -> ExportedVote {
+        let secs = at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        ExportedVote {
+            user_id: user_id.to_string(),
+            voted_at: format_utc(secs),
+            voted_at_unix: secs,
+        }
+    }
+}
+
+
+/// Writes the votes as CSV with a header row.
+pub(crate) fn write_csv(path: &Path, votes: &[(Snowflake, SystemTime)]) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "user_id,voted_at,voted_at_unix")?;
+    for (user_id, at) in votes {
+        let vote = ExportedVote::new(*user_id, *at);
+        writeln!(file, "{},{},{}", vote.user_id, vote.voted_at, vote.voted_at_unix)?;
+    }
+    file.flush()
+}
+
+
+/// Writes the votes as a JSON array of objects.
+pub(crate) fn write_json(path: &Path, votes: &[(Snowflake, SystemTime)]) -> io::Result<()> {
+    let votes = votes.iter().map(|(user_id, at)| ExportedVote::new(*user_id, *at)).collect::<Vec<_>>();
+    let mut file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut file, &votes)?;
+    file.flush()
+}
+
+
+/// Formats seconds since the epoch like `2024-03-01T18:26:14Z`.
+fn format_utc(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let time = secs % 86400;
+    // Howard Hinnant's civil_from_days.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
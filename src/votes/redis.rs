@@ -0,0 +1,160 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use ::redis::aio::ConnectionManager;
+use async_trait::async_trait;
+
+use super::{VoteStore, VoteStoreError, VOTE_DURATION};
+use crate::Snowflake;
+
+
+
+/// Deletes the vote only if it's still the one made at the given time, so a vote made again since it
+/// was read isn't lost.
+const EXPIRE_IF: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+
+/// Keeps votes in Redis so every process of a sharded bot sees the same votes. Each vote is a key
+/// holding the time it was made which Redis deletes by itself an hour after the vote runs out, leaving
+/// time for `VoteManager::watch_expirations` to notice it ran out first. Needs the `redis` feature.
+/// ## Examples
+/// ```
+/// use topgg::votes::{RedisStore, VoteManager};
+///
+/// let store = RedisStore::connect("redis://127.0.0.1/").await?;
+/// let votes = VoteManager::with_store(store);
+/// ```
+#[derive(Clone)]
+pub struct RedisStore {
+    conn: ConnectionManager,
+    prefix: String,
+    ttl: Duration,
+}
+impl RedisStore {
+    /// Connects to the Redis server at `url`, reconnecting by itself if the connection drops.
+    pub async fn connect(url: &str) -> Result<RedisStore, VoteStoreError> {
+        let client = ::redis::Client::open(url).map_err(VoteStoreError::new)?;
+        let conn = ConnectionManager::new(client).await.map_err(VoteStoreError::new)?;
+        Ok(RedisStore::new(conn))
+    }
+
+
+    /// Returns a store using an existing connection.
+    pub fn new(conn: ConnectionManager) -> RedisStore {
+        RedisStore {
+            conn,
+            prefix: "topgg:vote".to_string(),
+            ttl: VOTE_DURATION + Duration::from_secs(60 * 60),
+        }
+    }
+
+
+    /// Sets what the keys start with, `topgg:vote` by default, so several bots can share a database.
+    pub fn prefix(mut self, prefix: &str) -> RedisStore {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+
+    /// Sets how long after a vote its key is deleted, 13 hours by default. This should be a little
+    /// longer than the duration set on the `VoteManager`.
+    pub fn ttl(mut self, ttl: Duration) -> RedisStore {
+        self.ttl = ttl;
+        self
+    }
+
+
+    fn key(&self, user_id: Snowflake) -> String {
+        format!("{}:{}", self.prefix, user_id)
+    }
+}
+#[async_trait]
+impl VoteStore for RedisStore {
+    async fn record(&self, user_id: Snowflake, at: SystemTime) -> Result<(), VoteStoreError> {
+        let age = SystemTime::now().duration_since(at).unwrap_or_default();
+        let remaining = match self.ttl.checked_sub(age) {
+            Some(remaining) if remaining.as_secs() > 0 => remaining,
+            _ => return self.expire(user_id).await,
+        };
+        let at = at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        ::redis::cmd("SET")
+            .arg(self.key(user_id))
+            .arg(at)
+            .arg("EX")
+            .arg(remaining.as_secs())
+            .query_async::<_, ()>(&mut self.conn.clone())
+            .await
+            .map_err(VoteStoreError::new)
+    }
+
+
+    async fn get(&self, user_id: Snowflake) -> Result<Option<SystemTime>, VoteStoreError> {
+        let at: Option<u64> = ::redis::cmd("GET")
+            .arg(self.key(user_id))
+            .query_async(&mut self.conn.clone())
+            .await
+            .map_err(VoteStoreError::new)?;
+        Ok(at.map(|at| UNIX_EPOCH + Duration::from_secs(at)))
+    }
+
+
+    async fn expire(&self, user_id: Snowflake) -> Result<(), VoteStoreError> {
+        ::redis::cmd("DEL")
+            .arg(self.key(user_id))
+            .query_async::<_, ()>(&mut self.conn.clone())
+            .await
+            .map_err(VoteStoreError::new)
+    }
+
+
+    async fn expire_if(&self, user_id: Snowflake, voted_at: SystemTime) -> Result<bool, VoteStoreError> {
+        let at = voted_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let deleted: u32 = ::redis::Script::new(EXPIRE_IF)
+            .key(self.key(user_id))
+            .arg(at)
+            .invoke_async(&mut self.conn.clone())
+            .await
+            .map_err(VoteStoreError::new)?;
+        Ok(deleted > 0)
+    }
+
+
+    async fn iter(&self) -> Result<Vec<(Snowflake, SystemTime)>, VoteStoreError> {
+        let mut conn = self.conn.clone();
+        let pattern = format!("{}:*", self.prefix);
+        let mut keys = Vec::new();
+        let mut cursor = 0u64;
+        loop {
+            let (next, batch): (u64, Vec<String>) = ::redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(100)
+                .query_async(&mut conn)
+                .await
+                .map_err(VoteStoreError::new)?;
+            keys.extend(batch);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+
+        let mut votes = Vec::new();
+        for key in keys {
+            let user_id = match key[self.prefix.len() + 1..].parse::<Snowflake>() {
+                Ok(user_id) => user_id,
+                Err(_) => continue,
+            };
+            // The key may have run out since it was listed.
+            if let Some(at) = self.get(user_id).await? {
+                votes.push((user_id, at));
+            }
+        }
+        Ok(votes)
+    }
+}
@@ -0,0 +1,118 @@
+use std::time::{Duration, SystemTime};
+use futures::Future;
+
+use super::{MemoryStore, VoteStore, VoteStoreError, VOTE_DURATION};
+use crate::webhook::Event;
+use crate::Snowflake;
+
+
+
+/// How long `VoteReminders::run` waits at most before checking the store again, so reminders
+/// scheduled by other processes sharing the store are picked up.
+const MAX_WAIT: Duration = Duration::from_secs(60);
+
+
+/// Calls back once a user's vote is old enough for them to vote again, for sending "you can vote
+/// again" messages. Reminders are kept in a `VoteStore`, so with a `SqliteStore` or `RedisStore` they
+/// survive restarts. The store should be a different one from a `VoteManager`'s, such as another
+/// database file or Redis prefix.
+/// ## Examples
+/// ```
+/// use std::sync::Arc;
+/// use futures::StreamExt;
+/// use topgg::votes::VoteReminders;
+///
+/// let reminders = Arc::new(VoteReminders::new());
+/// let runner = reminders.clone();
+/// tokio::spawn(async move {
+///     runner.run(|user_id| async move { send_dm(user_id, "You can vote again!").await }).await
+/// });
+///
+/// let (mut events, _handle) = topgg::WebhookClient::start(3030, auth)?;
+/// while let Some(event) = events.next().await {
+///     reminders.schedule(&event).await?;
+/// }
+/// ```
+pub struct VoteReminders<S = MemoryStore> {
+    delay: Duration,
+    store: S,
+}
+impl VoteReminders<MemoryStore> {
+    /// Returns new reminders which are kept in memory, so they're lost on restart.
+    pub fn new() -> VoteReminders<MemoryStore> {
+        VoteReminders::with_store(MemoryStore::new())
+    }
+}
+impl<S: VoteStore> VoteReminders<S> {
+    /// Returns new reminders which are kept in the given store.
+    /// ## Examples
+    /// ```
+    /// let reminders = topgg::votes::VoteReminders::with_store(SqliteStore::open("reminders.db")?);
+    /// ```
+    pub fn with_store(store: S) -> VoteReminders<S> {
+        VoteReminders {
+            delay: VOTE_DURATION,
+            store,
+        }
+    }
+
+
+    /// Sets how long after a vote the reminder is due, instead of 12 hours.
+    pub fn with_delay(mut self, delay: Duration) -> VoteReminders<S> {
+        self.delay = delay;
+        self
+    }
+
+
+    /// Schedules a reminder for the user who voted in a webhook. Test webhooks are ignored.
+    pub async fn schedule(&self, event: &Event) -> Result<(), VoteStoreError> {
+        match event {
+            Event::BotVote(_) | Event::GuildVote(_) => self.schedule_user(event.user()).await,
+            Event::Test(_) => Ok(()),
+        }
+    }
+
+
+    /// Schedules a reminder for the user as if they voted just now, replacing any reminder they had.
+    pub async fn schedule_user(&self, user_id: impl Into<Snowflake>) -> Result<(), VoteStoreError> {
+        self.store.record(user_id.into(), SystemTime::now()).await
+    }
+
+
+    /// Cancels the user's reminder, if they have one.
+    pub async fn cancel(&self, user_id: impl Into<Snowflake>) -> Result<(), VoteStoreError> {
+        self.store.expire(user_id.into()).await
+    }
+
+
+    /// Calls `remind` with the ID of each user whose reminder is due, forever. A reminder is only
+    /// removed once `remind` has finished, so one interrupted by a restart is sent again afterwards.
+    /// If the store fails the error is returned and no more reminders are sent.
+    pub async fn run<F, Fut>(&self, remind: F) -> Result<(), VoteStoreError>
+    where
+        F: Fn(Snowflake) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            let now = SystemTime::now();
+            let mut wait = MAX_WAIT;
+            for (user_id, voted_at) in self.store.iter().await? {
+                let due = voted_at + self.delay;
+                match due.duration_since(now) {
+                    Ok(until_due) if !until_due.is_zero() => wait = wait.min(until_due),
+                    _ => {
+                        remind(user_id).await;
+                        // The user may have voted again while being reminded.
+                        self.store.expire_if(user_id, voted_at).await?;
+                    }
+                }
+            }
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+impl Default for VoteReminders<MemoryStore> {
+    fn default() -> VoteReminders<MemoryStore> {
+        VoteReminders::new()
+    }
+}
@@ -0,0 +1,131 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{VoteStore, VoteStoreError};
+use crate::Snowflake;
+
+
+
+/// The changes to the schema, in order. The number applied so far is kept in `user_version`, so new
+/// ones can be added to the end without touching databases that already have the earlier ones.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE topgg_votes (
+        user_id INTEGER PRIMARY KEY NOT NULL,
+        voted_at INTEGER NOT NULL
+    );",
+];
+
+
+/// Keeps votes in a SQLite database file, so they survive restarts without running a database
+/// server. The table is created the first time the database is opened. Needs the `sqlite` feature.
+///
+/// Queries are made on the calling task since SQLite reads and writes a local file quickly.
+/// ## Examples
+/// ```
+/// use topgg::votes::{SqliteStore, VoteManager};
+///
+/// let votes = VoteManager::with_store(SqliteStore::open("votes.db")?);
+/// ```
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+impl SqliteStore {
+    /// Opens the database at `path`, creating it if it doesn't exist yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<SqliteStore, VoteStoreError> {
+        let conn = Connection::open(path).map_err(VoteStoreError::new)?;
+        SqliteStore::new(conn)
+    }
+
+
+    /// Returns a store using an existing connection, bringing its schema up to date.
+    pub fn new(conn: Connection) -> Result<SqliteStore, VoteStoreError> {
+        migrate(&conn).map_err(VoteStoreError::new)?;
+        Ok(SqliteStore {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+#[async_trait]
+impl VoteStore for SqliteStore {
+    async fn record(&self, user_id: Snowflake, at: SystemTime) -> Result<(), VoteStoreError> {
+        let at = at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO topgg_votes (user_id, voted_at) VALUES (?1, ?2)
+                 ON CONFLICT (user_id) DO UPDATE SET voted_at = excluded.voted_at",
+                params![user_id.get() as i64, at],
+            )
+            .map(|_| ())
+            .map_err(VoteStoreError::new)
+    }
+
+
+    async fn get(&self, user_id: Snowflake) -> Result<Option<SystemTime>, VoteStoreError> {
+        let at: Option<i64> = self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT voted_at FROM topgg_votes WHERE user_id = ?1",
+                params![user_id.get() as i64],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(VoteStoreError::new)?;
+        Ok(at.map(to_time))
+    }
+
+
+    async fn expire(&self, user_id: Snowflake) -> Result<(), VoteStoreError> {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("DELETE FROM topgg_votes WHERE user_id = ?1", params![user_id.get() as i64])
+            .map(|_| ())
+            .map_err(VoteStoreError::new)
+    }
+
+
+    async fn expire_if(&self, user_id: Snowflake, voted_at: SystemTime) -> Result<bool, VoteStoreError> {
+        let at = voted_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "DELETE FROM topgg_votes WHERE user_id = ?1 AND voted_at = ?2",
+                params![user_id.get() as i64, at],
+            )
+            .map(|deleted| deleted > 0)
+            .map_err(VoteStoreError::new)
+    }
+
+
+    async fn iter(&self) -> Result<Vec<(Snowflake, SystemTime)>, VoteStoreError> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn
+            .prepare("SELECT user_id, voted_at FROM topgg_votes")
+            .map_err(VoteStoreError::new)?;
+        let rows = statement
+            .query_map([], |row| Ok((Snowflake(row.get::<_, i64>(0)? as u64), to_time(row.get(1)?))))
+            .map_err(VoteStoreError::new)?;
+        rows.collect::<Result<_, _>>().map_err(VoteStoreError::new)
+    }
+}
+
+
+fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        conn.execute_batch(&format!("BEGIN; {} PRAGMA user_version = {}; COMMIT;", migration, i + 1))?;
+    }
+    Ok(())
+}
+
+
+fn to_time(secs: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs.max(0) as u64)
+}
@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::Snowflake;
+
+pub(crate) mod events;
+pub use events::{Events, Overflow};
+#[cfg(feature = "webhook")]
+mod metrics;
+#[cfg(feature = "webhook")]
+pub use metrics::WebhookMetrics;
+#[cfg(feature = "webhook")]
+mod dedup;
+#[cfg(feature = "webhook")]
+mod journal;
+#[cfg(feature = "webhook")]
+pub use journal::Journal;
+#[cfg(any(feature = "forward", feature = "redis", feature = "kafka", feature = "nats"))]
+mod sink;
+#[cfg(any(feature = "forward", feature = "redis", feature = "kafka", feature = "nats"))]
+pub use sink::{EventSink, SinkError};
+#[cfg(feature = "forward")]
+pub use sink::HttpForwarder;
+#[cfg(feature = "redis")]
+pub use sink::RedisPublisher;
+#[cfg(feature = "kafka")]
+pub use sink::KafkaSink;
+#[cfg(feature = "nats")]
+pub use sink::NatsSink;
+#[cfg(feature = "relay")]
+mod relay;
+#[cfg(feature = "relay")]
+pub use relay::{DiscordRelay, RelayError};
+#[cfg(feature = "webhook")]
+mod ack;
+#[cfg(feature = "webhook")]
+pub use ack::{AckQueue, DeadLetter, DeadLetters, Delivery};
+#[cfg(feature = "webhook")]
+mod live;
+#[cfg(feature = "webhook")]
+mod server;
+#[cfg(feature = "webhook")]
+pub use server::{filter, recover, RoutedEvent, WebhookClient, WebhookHandle};
+#[cfg(feature = "webhook")]
+pub use ipnet::IpNet;
+#[cfg(feature = "webhook")]
+pub use governor::Quota;
+
+
+
+/// How many unread events are queued for each subscriber unless configured otherwise.
+#[cfg_attr(not(any(feature = "webhook", feature = "axum", feature = "rocket", feature = "tower", feature = "votes")), allow(dead_code))]
+pub(crate) const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+
+
+/// Checks the authorization header of a webhook request and parses its body. This is what the
+/// built in server does, for receiving webhooks with any other HTTP server.
+///
+/// ## Arguments
+/// * `auth` - The authorization string set on your top.gg webhook page
+/// * `authorization` - The value of the `Authorization` header of the request, if it had one
+/// * `body` - The body of the request
+///
+/// ## Examples
+/// ```
+/// match topgg::webhook::parse(&auth, headers.get("authorization"), &body) {
+///     Ok(event) => println!("{:?}", event),
+///     Err(e) => respond(e.status()),
+/// }
+/// ```
+pub fn parse(auth: &str, authorization: Option<&str>, body: &[u8]) -> Result<Event, WebhookError> {
+    if !is_authorized(&[auth], authorization) {
+        return Err(WebhookError::Unauthorized);
+    }
+    parse_body(body)
+}
+
+
+/// Parses the body of a webhook request whose authorization has already been checked.
+pub(crate) fn parse_body(body: &[u8]) -> Result<Event, WebhookError> {
+    serde_json::from_slice(body).map_err(WebhookError::InvalidBody)
+}
+
+
+/// Whether the authorization header matches any of the accepted strings. Every one of them is compared
+/// in constant time so how long the check takes doesn't give away how close a guess was.
+pub(crate) fn is_authorized<S: AsRef<str>>(accepted: &[S], authorization: Option<&str>) -> bool {
+    let authorization = match authorization {
+        Some(authorization) => authorization.as_bytes(),
+        None => return false,
+    };
+    accepted
+        .iter()
+        .fold(Choice::from(0), |matched, auth| matched | auth.as_ref().as_bytes().ct_eq(authorization))
+        .into()
+}
+
+
+/// Like `parse`, but finds the authorization header among all the headers of the request. This
+/// doesn't depend on any HTTP library or runtime, so it can be used on Cloudflare Workers and other
+/// WASM runtimes (with `default-features = false`).
+/// ## Examples
+/// ```
+/// // In a Cloudflare Worker
+/// let body = req.bytes().await?;
+/// match topgg::webhook::parse_request(&auth, req.headers().entries(), &body) {
+///     Ok(event) => Response::ok("ok"),
+///     Err(e) => Response::error(e.to_string(), e.status()),
+/// }
+/// ```
+pub fn parse_request<I, K, V>(auth: &str, headers: I, body: &[u8]) -> Result<Event, WebhookError>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let authorization = headers
+        .into_iter()
+        .find(|(name, _)| name.as_ref().eq_ignore_ascii_case("authorization"))
+        .map(|(_, value)| value);
+    parse(auth, authorization.as_ref().map(|value| value.as_ref()), body)
+}
+
+
+/// Why a webhook request was refused by `parse`.
+#[derive(Debug)]
+pub enum WebhookError {
+    /// The authorization header was missing or wrong.
+    Unauthorized,
+    /// The body wasn't a webhook from top.gg.
+    InvalidBody(serde_json::Error),
+}
+impl WebhookError {
+    /// The HTTP status code to respond with.
+    pub fn status(&self) -> u16 {
+        match self {
+            WebhookError::Unauthorized => 401,
+            WebhookError::InvalidBody(_) => 400,
+        }
+    }
+}
+impl std::fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookError::Unauthorized => f.write_str("Unauthorized"),
+            WebhookError::InvalidBody(e) => write!(f, "invalid webhook body: {}", e),
+        }
+    }
+}
+impl std::error::Error for WebhookError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WebhookError::Unauthorized => None,
+            WebhookError::InvalidBody(e) => Some(e),
+        }
+    }
+}
+
+
+/// A vote for a bot listed on top.gg.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub bot: Snowflake,
+    pub user: Snowflake,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub is_weekend: bool,
+    pub query: Option<String>,
+}
+impl Webhook {
+    /// The query parameters of the vote page the user voted on, like `?ref=twitter` when they followed
+    /// `https://top.gg/bot/:id/vote?ref=twitter`. Empty if there were none.
+    /// ## Examples
+    /// ```
+    /// let source = vote.query_params().get("ref").cloned();
+    /// ```
+    pub fn query_params(&self) -> HashMap<String, String> {
+        query_as(&self.query).unwrap_or_default()
+    }
+
+
+    /// Parses the query parameters of the vote page the user voted on into `T`.
+    /// ## Examples
+    /// ```
+    /// #[derive(serde::Deserialize)]
+    /// struct Campaign {
+    ///     campaign: String,
+    ///     referrer: Option<u64>,
+    /// }
+    ///
+    /// let campaign = vote.query_as::<Campaign>()?;
+    /// ```
+    pub fn query_as<T: DeserializeOwned>(&self) -> Result<T, serde_urlencoded::de::Error> {
+        query_as(&self.query)
+    }
+
+
+    /// How many points the vote is worth, 2 if it was made on a weekend and 1 otherwise.
+    /// ## Examples
+    /// ```
+    /// db.add_points(vote.user, vote.vote_value()).await;
+    /// ```
+    pub fn vote_value(&self) -> u32 {
+        vote_value(self.is_weekend)
+    }
+}
+
+
+/// How many points a vote is worth depending on whether it was made on a weekend, when votes on
+/// top.gg count twice.
+pub fn vote_value(is_weekend: bool) -> u32 {
+    if is_weekend {
+        2
+    } else {
+        1
+    }
+}
+
+
+/// A vote for a server listed on top.gg.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GuildWebhook {
+    pub guild: Snowflake,
+    pub user: Snowflake,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub query: Option<String>,
+}
+impl GuildWebhook {
+    /// The query parameters of the vote page the user voted on. See `Webhook::query_params`.
+    pub fn query_params(&self) -> HashMap<String, String> {
+        query_as(&self.query).unwrap_or_default()
+    }
+
+
+    /// Parses the query parameters of the vote page the user voted on into `T`. See `Webhook::query_as`.
+    pub fn query_as<T: DeserializeOwned>(&self) -> Result<T, serde_urlencoded::de::Error> {
+        query_as(&self.query)
+    }
+}
+
+
+fn query_as<T: DeserializeOwned>(query: &Option<String>) -> Result<T, serde_urlencoded::de::Error> {
+    let query = query.as_deref().unwrap_or("");
+    serde_urlencoded::from_str(query.trim_start_matches('?'))
+}
+
+
+/// The body of a webhook, which is either for a bot or a server depending on which page it was set up on.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Vote {
+    Bot(Webhook),
+    Guild(GuildWebhook),
+}
+impl Vote {
+    /// The ID of the user who voted.
+    pub fn user(&self) -> Snowflake {
+        match self {
+            Vote::Bot(vote) => vote.user,
+            Vote::Guild(vote) => vote.user,
+        }
+    }
+
+
+    /// The type of the vote, `upvote` or `test`.
+    pub fn kind(&self) -> &str {
+        match self {
+            Vote::Bot(vote) => &vote.kind,
+            Vote::Guild(vote) => &vote.kind,
+        }
+    }
+}
+
+
+/// An event received by the webhook server.
+/// ## Examples
+/// ```
+/// use topgg::Event;
+///
+/// while let Some(event) = events.next().await {
+///     match event {
+///         Event::BotVote(vote) => println!("{} voted for {}", vote.user, vote.bot),
+///         Event::GuildVote(vote) => println!("{} voted for {}", vote.user, vote.guild),
+///         Event::Test(_) => println!("test received"),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(from = "Vote", into = "Vote")]
+pub enum Event {
+    /// A user voted for a bot.
+    BotVote(Webhook),
+    /// A user voted for a server.
+    GuildVote(GuildWebhook),
+    /// A test sent from the webhook settings on top.gg, which shouldn't be rewarded.
+    Test(Vote),
+}
+impl Event {
+    /// The ID of the user who voted, or who sent the test.
+    pub fn user(&self) -> Snowflake {
+        match self {
+            Event::BotVote(vote) => vote.user,
+            Event::GuildVote(vote) => vote.user,
+            Event::Test(vote) => vote.user(),
+        }
+    }
+}
+impl From<Vote> for Event {
+    fn from(vote: Vote) -> Event {
+        if vote.kind() == "test" {
+            return Event::Test(vote);
+        }
+        match vote {
+            Vote::Bot(vote) => Event::BotVote(vote),
+            Vote::Guild(vote) => Event::GuildVote(vote),
+        }
+    }
+}
+impl From<Event> for Vote {
+    fn from(event: Event) -> Vote {
+        match event {
+            Event::BotVote(vote) => Vote::Bot(vote),
+            Event::GuildVote(vote) => Vote::Guild(vote),
+            Event::Test(vote) => vote,
+        }
+    }
+}
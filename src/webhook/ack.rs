@@ -0,0 +1,390 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant, SystemTime};
+use futures::{Future, Stream};
+use serde::{Deserialize, Serialize};
+
+use super::events::Events;
+use super::server::RoutedEvent;
+use super::Event;
+
+
+
+/// Hands out events which have to be acknowledged once they're handled. An event that isn't
+/// acknowledged in time, is rejected with `Delivery::nack` or whose `Delivery` is dropped is handed
+/// out again, and after too many attempts it's moved to a `DeadLetters` store for inspecting later.
+/// Made with `WebhookHandle::subscribe_acked`.
+/// ## Examples
+/// ```
+/// use futures::StreamExt;
+/// use topgg::webhook::DeadLetters;
+///
+/// let dead_letters = DeadLetters::open("dead-votes.jsonl")?;
+/// let mut deliveries = handle
+///     .subscribe_acked(dead_letters.clone())
+///     .timeout(Duration::from_secs(60))
+///     .max_attempts(3);
+/// while let Some(delivery) = deliveries.next().await {
+///     match grant_reward(&delivery.event).await {
+///         Ok(()) => delivery.ack(),
+///         Err(_) => delivery.nack(),
+///     }
+/// }
+/// ```
+pub struct AckQueue {
+    events: Events<RoutedEvent>,
+    events_done: bool,
+    state: Arc<Mutex<State>>,
+    dead_letters: DeadLetters,
+    next_key: u64,
+    timeout: Duration,
+    max_attempts: u32,
+    retry_delay: Duration,
+    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+#[derive(Default)]
+struct State {
+    in_flight: HashMap<u64, Pending>,
+    retry: VecDeque<Pending>,
+    /// Deliveries which were rejected or dropped, for the queue to retry or give up on.
+    failed: Vec<Pending>,
+    waker: Option<Waker>,
+}
+impl State {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// An event waiting to be acknowledged, or to be handed out again once `due`.
+struct Pending {
+    key: u64,
+    routed: RoutedEvent,
+    attempts: u32,
+    due: Instant,
+}
+
+impl AckQueue {
+    /// Hands out the events from `events`, moving the ones that keep failing to `dead_letters`.
+    pub fn new(events: Events<RoutedEvent>, dead_letters: DeadLetters) -> AckQueue {
+        AckQueue {
+            events,
+            events_done: false,
+            state: Arc::new(Mutex::new(State::default())),
+            dead_letters,
+            next_key: 0,
+            timeout: Duration::from_secs(30),
+            max_attempts: 5,
+            retry_delay: Duration::from_secs(1),
+            sleep: None,
+        }
+    }
+
+
+    /// Sets how long a delivery has to be acknowledged in before it's handed out again, 30 seconds by default.
+    pub fn timeout(mut self, timeout: Duration) -> AckQueue {
+        self.timeout = timeout;
+        self
+    }
+
+
+    /// Sets how many times an event is handed out before it's moved to the dead letters, 5 by default.
+    pub fn max_attempts(mut self, max_attempts: u32) -> AckQueue {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+
+    /// Sets how long a failed event waits before it's handed out again, a second by default.
+    pub fn retry_delay(mut self, delay: Duration) -> AckQueue {
+        self.retry_delay = delay;
+        self
+    }
+
+
+    fn deliver(&mut self, state: &mut State, key: u64, routed: RoutedEvent, attempts: u32) -> Delivery {
+        let delivery = Delivery {
+            id: routed.id,
+            route: routed.route.clone(),
+            event: routed.event.clone(),
+            attempt: attempts,
+            key,
+            state: self.state.clone(),
+            settled: false,
+        };
+        state.in_flight.insert(
+            key,
+            Pending {
+                key,
+                routed,
+                attempts,
+                due: Instant::now() + self.timeout,
+            },
+        );
+        delivery
+    }
+
+
+    /// Queues the event to be handed out again, or gives up on it if it's been tried enough times.
+    fn fail(&self, state: &mut State, mut pending: Pending, now: Instant) {
+        if pending.attempts >= self.max_attempts {
+            self.dead_letters.push(DeadLetter {
+                id: pending.routed.id,
+                route: pending.routed.route,
+                event: pending.routed.event,
+                attempts: pending.attempts,
+                failed_at: SystemTime::now(),
+            });
+            return;
+        }
+        pending.due = now + self.retry_delay;
+        state.retry.push_back(pending);
+    }
+}
+impl Stream for AckQueue {
+    type Item = Delivery;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Delivery>> {
+        let this = self.get_mut();
+        loop {
+            let now = Instant::now();
+            let state = this.state.clone();
+            let mut state = state.lock().unwrap();
+            state.waker = Some(cx.waker().clone());
+
+            let expired = state
+                .in_flight
+                .values()
+                .filter(|pending| pending.due <= now)
+                .map(|pending| pending.key)
+                .collect::<Vec<_>>();
+            let mut failed = std::mem::take(&mut state.failed);
+            failed.extend(expired.into_iter().filter_map(|key| state.in_flight.remove(&key)));
+            // Oldest first, so retries keep roughly the order events arrived in.
+            failed.sort_by_key(|pending| pending.key);
+            for pending in failed {
+                this.fail(&mut state, pending, now);
+            }
+
+            if matches!(state.retry.front(), Some(pending) if pending.due <= now) {
+                if let Some(pending) = state.retry.pop_front() {
+                    let delivery = this.deliver(&mut state, pending.key, pending.routed, pending.attempts + 1);
+                    return Poll::Ready(Some(delivery));
+                }
+            }
+
+            if !this.events_done {
+                match Pin::new(&mut this.events).poll_next(cx) {
+                    Poll::Ready(Some(routed)) => {
+                        let key = this.next_key;
+                        this.next_key += 1;
+                        return Poll::Ready(Some(this.deliver(&mut state, key, routed, 1)));
+                    }
+                    Poll::Ready(None) => this.events_done = true,
+                    Poll::Pending => {}
+                }
+            }
+            if this.events_done && state.in_flight.is_empty() && state.retry.is_empty() {
+                return Poll::Ready(None);
+            }
+
+            let next_due = state
+                .in_flight
+                .values()
+                .map(|pending| pending.due)
+                .chain(state.retry.front().map(|pending| pending.due))
+                .min();
+            drop(state);
+            if let Some(due) = next_due {
+                let due = tokio::time::Instant::from(due);
+                match &mut this.sleep {
+                    Some(sleep) => sleep.as_mut().reset(due),
+                    None => this.sleep = Some(Box::pin(tokio::time::sleep_until(due))),
+                }
+                if let Some(sleep) = &mut this.sleep {
+                    if sleep.as_mut().poll(cx).is_ready() {
+                        continue;
+                    }
+                }
+            }
+            return Poll::Pending;
+        }
+    }
+}
+
+
+/// An event handed out by an `AckQueue`. Call `ack` once it's been handled, or `nack` to have it
+/// retried; dropping it without either counts as a `nack`.
+pub struct Delivery {
+    /// The ID to acknowledge the event with in a `Journal`, if one is used. Acknowledging the
+    /// delivery doesn't acknowledge it in the journal.
+    pub id: Option<u64>,
+    pub route: Option<String>,
+    pub event: Event,
+    /// Which attempt at handling the event this is, starting from 1.
+    pub attempt: u32,
+    key: u64,
+    state: Arc<Mutex<State>>,
+    settled: bool,
+}
+impl Delivery {
+    /// Marks the event as handled, so it isn't handed out again. An acknowledgement arriving after the
+    /// timeout still stops any retry that hasn't been handed out yet.
+    pub fn ack(mut self) {
+        self.settled = true;
+        let mut state = self.state.lock().unwrap();
+        state.in_flight.remove(&self.key);
+        state.retry.retain(|pending| pending.key != self.key);
+        state.wake();
+    }
+
+
+    /// Marks the event as failed, so it's handed out again after the retry delay or moved to the
+    /// dead letters if it's been tried enough times.
+    pub fn nack(mut self) {
+        self.settled = true;
+        self.fail();
+    }
+
+
+    fn fail(&self) {
+        let mut state = self.state.lock().unwrap();
+        // After timing out the event may have been handed out again, which is then the one to settle it.
+        let current = matches!(state.in_flight.get(&self.key), Some(pending) if pending.attempts == self.attempt);
+        if current {
+            if let Some(pending) = state.in_flight.remove(&self.key) {
+                state.failed.push(pending);
+                state.wake();
+            }
+        }
+    }
+}
+impl Drop for Delivery {
+    fn drop(&mut self) {
+        if !self.settled {
+            self.fail();
+        }
+    }
+}
+impl std::fmt::Debug for Delivery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Delivery")
+            .field("id", &self.id)
+            .field("route", &self.route)
+            .field("event", &self.event)
+            .field("attempt", &self.attempt)
+            .finish()
+    }
+}
+
+
+/// An event that was given up on after failing every attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// The event's ID in the `Journal`, if one is used.
+    pub id: Option<u64>,
+    pub route: Option<String>,
+    pub event: Event,
+    pub attempts: u32,
+    pub failed_at: SystemTime,
+}
+
+
+/// Where an `AckQueue` puts the events it gave up on, kept in memory or also appended to a file as
+/// lines of JSON so they survive restarts.
+#[derive(Clone, Default)]
+pub struct DeadLetters {
+    inner: Arc<Mutex<DeadLettersInner>>,
+}
+
+#[derive(Default)]
+struct DeadLettersInner {
+    file: Option<File>,
+    letters: Vec<DeadLetter>,
+}
+
+impl DeadLetters {
+    /// Returns an empty store kept in memory, so its events are lost on restart.
+    pub fn new() -> DeadLetters {
+        DeadLetters::default()
+    }
+
+
+    /// Opens the store at `path`, creating it if it doesn't exist yet, and reads back the events already
+    /// in it. A line cut short by a crash while it was being written is skipped.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<DeadLetters> {
+        let path = path.as_ref();
+        let mut letters = Vec::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(path)?).lines() {
+                if let Ok(letter) = serde_json::from_str(&line?) {
+                    letters.push(letter);
+                }
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(DeadLetters {
+            inner: Arc::new(Mutex::new(DeadLettersInner {
+                file: Some(file),
+                letters,
+            })),
+        })
+    }
+
+
+    /// Every event in the store, oldest first.
+    pub fn list(&self) -> Vec<DeadLetter> {
+        self.inner.lock().unwrap().letters.clone()
+    }
+
+
+    /// How many events are in the store.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().letters.len()
+    }
+
+
+    /// Whether the store is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+
+    /// Removes every event from the store and returns them, such as to handle them by hand.
+    pub fn take(&self) -> io::Result<Vec<DeadLetter>> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(file) = &inner.file {
+            file.set_len(0)?;
+        }
+        Ok(std::mem::take(&mut inner.letters))
+    }
+
+
+    /// Adds the event, keeping it in memory even if it can't be written to the file.
+    fn push(&self, letter: DeadLetter) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(file) = &mut inner.file {
+            if let Err(e) = write_letter(file, &letter) {
+                event!(tracing::Level::ERROR, error = %e, "writing a dead letter failed");
+            }
+        }
+        inner.letters.push(letter);
+    }
+}
+
+
+fn write_letter(file: &mut File, letter: &DeadLetter) -> io::Result<()> {
+    let mut line = serde_json::to_vec(letter)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    file.sync_data()
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::Event;
+use crate::Snowflake;
+
+
+
+/// Remembers which votes were seen recently so that the same vote delivered twice, such as when
+/// top.gg retries a delivery it thinks failed, only reaches the subscribers once.
+pub(crate) struct Dedup {
+    window: Duration,
+    state: Mutex<State>,
+}
+
+struct State {
+    seen: HashMap<(Snowflake, Snowflake), Instant>,
+    last_pruned: Instant,
+}
+
+impl Dedup {
+    pub(crate) fn new(window: Duration) -> Dedup {
+        Dedup {
+            window,
+            state: Mutex::new(State {
+                seen: HashMap::new(),
+                last_pruned: Instant::now(),
+            }),
+        }
+    }
+
+
+    /// Records the vote, returning whether it's the first time it was seen within the window.
+    pub(crate) fn first_seen(&self, key: (Snowflake, Snowflake)) -> bool {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+
+        if now.duration_since(state.last_pruned) >= self.window {
+            let window = self.window;
+            state.seen.retain(|_, seen| now.duration_since(*seen) < window);
+            state.last_pruned = now;
+        }
+
+        match state.seen.get(&key) {
+            Some(seen) if now.duration_since(*seen) < self.window => false,
+            _ => {
+                state.seen.insert(key, now);
+                true
+            }
+        }
+    }
+
+
+    /// Forgets the vote, for when it couldn't be queued and top.gg will deliver it again.
+    pub(crate) fn forget(&self, key: (Snowflake, Snowflake)) {
+        self.state.lock().unwrap().seen.remove(&key);
+    }
+}
+
+
+/// The bot or server voted for and the user who voted. Test webhooks have no key since they're
+/// never treated as repeats.
+pub(crate) fn key(event: &Event) -> Option<(Snowflake, Snowflake)> {
+    match event {
+        Event::BotVote(vote) => Some((vote.bot, vote.user)),
+        Event::GuildVote(vote) => Some((vote.guild, vote.user)),
+        Event::Test(_) => None,
+    }
+}
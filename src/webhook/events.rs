@@ -0,0 +1,262 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use futures::Stream;
+
+
+
+/// What happens to a webhook that arrives while the queue of unread events is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Throws away the oldest unread event to make room for the new one.
+    DropOldest,
+    /// Throws away the new event. top.gg is still told it was received.
+    DropNewest,
+    /// Responds to top.gg with a 503 so it retries the delivery later. A full queue rejects the
+    /// webhook for every subscriber, so one `Events` that's never read stalls all of them.
+    Reject,
+}
+
+
+pub(crate) enum SendError {
+    /// The queue is full and the overflow policy is `Overflow::Reject`.
+    Full,
+    /// Every `Events` receiving from the queue was dropped.
+    Closed,
+}
+
+
+/// A stream of the events received by the webhook server. It ends once the server has shut down,
+/// or once the filter from `WebhookClient::into_filter` has been dropped.
+pub struct Events<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Sends events of type `Q` into a queue of `T`s, converting them on the way.
+struct Sender<Q, T> {
+    shared: Arc<Shared<T>>,
+    map: fn(Q) -> T,
+}
+
+struct Shared<T> {
+    capacity: Option<usize>,
+    overflow: Overflow,
+    state: Mutex<State<T>>,
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    waker: Option<Waker>,
+    closed: bool,
+    receiver_alive: bool,
+}
+
+
+/// One subscriber of a `Broadcast`, hiding what type of event its queue holds.
+trait Subscriber<Q>: Send {
+    /// Queues the event, returning whether an event had to be thrown away to do so.
+    fn send(&self, event: Q) -> Result<bool, SendError>;
+
+    /// Whether the next event would be rejected because the queue is full.
+    fn is_full(&self) -> bool;
+
+    fn is_closed(&self) -> bool;
+
+    /// Ends the stream once the events already queued have been read.
+    fn close(&self);
+}
+
+
+/// Sends every event to each of its subscribers, each of which has its own queue.
+#[cfg_attr(not(any(feature = "webhook", feature = "axum", feature = "rocket", feature = "tower", feature = "votes")), allow(dead_code))]
+pub(crate) struct Broadcast<Q> {
+    capacity: Option<usize>,
+    overflow: Overflow,
+    subscribers: Mutex<Subscribers<Q>>,
+    dropped: AtomicU64,
+}
+
+struct Subscribers<Q> {
+    senders: Vec<Box<dyn Subscriber<Q>>>,
+    closed: bool,
+}
+
+#[cfg_attr(not(any(feature = "webhook", feature = "axum", feature = "rocket", feature = "tower", feature = "votes")), allow(dead_code))]
+impl<Q: Clone + Send + 'static> Broadcast<Q> {
+    pub(crate) fn new(capacity: Option<usize>, overflow: Overflow) -> Broadcast<Q> {
+        Broadcast {
+            capacity,
+            overflow,
+            subscribers: Mutex::new(Subscribers {
+                senders: Vec::new(),
+                closed: false,
+            }),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+
+    pub(crate) fn subscribe(&self) -> Events<Q> {
+        self.subscribe_map(|event| event)
+    }
+
+
+    /// Subscribes with a stream which gets every event after converting it with `map`.
+    pub(crate) fn subscribe_map<T: Send + 'static>(&self, map: fn(Q) -> T) -> Events<T> {
+        self.subscribe_with(self.overflow, map)
+    }
+
+
+    /// Like `subscribe_map`, but the stream's queue throws away its oldest event when full instead of
+    /// following the overflow policy, so a slow subscriber like a browser can't make webhooks be refused.
+    pub(crate) fn subscribe_lossy<T: Send + 'static>(&self, map: fn(Q) -> T) -> Events<T> {
+        self.subscribe_with(Overflow::DropOldest, map)
+    }
+
+
+    fn subscribe_with<T: Send + 'static>(&self, overflow: Overflow, map: fn(Q) -> T) -> Events<T> {
+        let (sender, events) = channel(self.capacity, overflow, map);
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if subscribers.closed {
+            sender.close();
+        } else {
+            subscribers.senders.push(Box::new(sender));
+        }
+        events
+    }
+
+
+    /// Sends the event to every subscriber. If any of their queues would reject it then nobody gets
+    /// it, so a retried delivery isn't seen twice by the subscribers that had room.
+    pub(crate) fn send(&self, event: Q) -> Result<(), SendError> {
+        // Holding the lock while sending means no other event can fill a queue between checking and sending.
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.senders.retain(|sender| !sender.is_closed());
+        if subscribers.senders.is_empty() {
+            return Err(SendError::Closed);
+        }
+        if subscribers.senders.iter().any(|sender| sender.is_full()) {
+            return Err(SendError::Full);
+        }
+
+        for sender in &subscribers.senders {
+            if let Ok(true) = sender.send(event.clone()) {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        Ok(())
+    }
+}
+impl<Q> Broadcast<Q> {
+    /// How many events have been thrown away because a subscriber's queue was full.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+
+    pub(crate) fn close(&self) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.closed = true;
+        for sender in &subscribers.senders {
+            sender.close();
+        }
+    }
+}
+impl<Q> Drop for Broadcast<Q> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+
+/// Makes a queue holding at most `capacity` unread events, or any number of them when `None`.
+fn channel<Q, T>(capacity: Option<usize>, overflow: Overflow, map: fn(Q) -> T) -> (Sender<Q, T>, Events<T>) {
+    let shared = Arc::new(Shared {
+        capacity,
+        overflow,
+        state: Mutex::new(State {
+            queue: VecDeque::new(),
+            waker: None,
+            closed: false,
+            receiver_alive: true,
+        }),
+    });
+    (Sender { shared: shared.clone(), map }, Events { shared })
+}
+
+
+impl<Q, T: Send> Subscriber<Q> for Sender<Q, T> {
+    fn send(&self, event: Q) -> Result<bool, SendError> {
+        let mut state = self.shared.state.lock().unwrap();
+        if !state.receiver_alive {
+            return Err(SendError::Closed);
+        }
+
+        let mut dropped = false;
+        if let Some(capacity) = self.shared.capacity {
+            if state.queue.len() >= capacity {
+                match self.shared.overflow {
+                    Overflow::DropOldest => {
+                        state.queue.pop_front();
+                        dropped = true;
+                    }
+                    Overflow::DropNewest => return Ok(true),
+                    Overflow::Reject => return Err(SendError::Full),
+                }
+            }
+        }
+
+        state.queue.push_back((self.map)(event));
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        Ok(dropped)
+    }
+
+
+    fn is_full(&self) -> bool {
+        let state = self.shared.state.lock().unwrap();
+        self.shared.overflow == Overflow::Reject
+            && self.shared.capacity.map_or(false, |capacity| state.queue.len() >= capacity)
+    }
+
+
+    fn is_closed(&self) -> bool {
+        !self.shared.state.lock().unwrap().receiver_alive
+    }
+
+
+    fn close(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.closed = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+
+impl<T> Stream for Events<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut state = self.shared.state.lock().unwrap();
+        if let Some(event) = state.queue.pop_front() {
+            return Poll::Ready(Some(event));
+        }
+        if state.closed {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+impl<T> Drop for Events<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.receiver_alive = false;
+        state.queue.clear();
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+
+use super::server::RoutedEvent;
+use super::Event;
+
+
+
+/// An append-only file where the webhook server writes down every event before telling top.gg it
+/// was received, so votes that weren't handled yet survive the process crashing or restarting.
+/// Each event is kept until it's acknowledged with `ack`, and the ones left over from before a
+/// restart are returned by `pending`.
+///
+/// Events are written as lines of JSON and acknowledgements are appended after them, so the file
+/// only grows until `compact` is called.
+/// ## Examples
+/// ```
+/// use futures::StreamExt;
+/// use topgg::webhook::Journal;
+///
+/// let journal = Journal::open("votes.jsonl")?;
+/// for routed in journal.pending() {
+///     grant_reward(&routed.event).await;
+///     journal.ack(routed.id.unwrap())?;
+/// }
+///
+/// let (_, handle) = topgg::WebhookClient::new(3030, auth)
+///     .journal(journal.clone())
+///     .listen()?;
+///
+/// let mut events = handle.subscribe_routed();
+/// while let Some(routed) = events.next().await {
+///     grant_reward(&routed.event).await;
+///     if let Some(id) = routed.id {
+///         journal.ack(id)?;
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Journal {
+    inner: Arc<Mutex<Inner>>,
+}
+
+struct Inner {
+    path: PathBuf,
+    file: File,
+    next_id: u64,
+    pending: BTreeMap<u64, (Option<String>, Event)>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Event {
+        id: u64,
+        route: Option<String>,
+        event: Event,
+    },
+    Ack {
+        ack: u64,
+    },
+}
+
+impl Journal {
+    /// Opens the journal at `path`, creating it if it doesn't exist yet, and reads back the events
+    /// that were never acknowledged. A line cut short by a crash while it was being written is skipped.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Journal> {
+        let path = path.as_ref().to_path_buf();
+        let mut next_id = 1;
+        let mut pending = BTreeMap::new();
+
+        if path.exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                match serde_json::from_str::<Entry>(&line?) {
+                    Ok(Entry::Event { id, route, event }) => {
+                        next_id = next_id.max(id + 1);
+                        pending.insert(id, (route, event));
+                    }
+                    Ok(Entry::Ack { ack }) => {
+                        pending.remove(&ack);
+                    }
+                    Err(_) => continue,
+                }
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Journal {
+            inner: Arc::new(Mutex::new(Inner {
+                path,
+                file,
+                next_id,
+                pending,
+            })),
+        })
+    }
+
+
+    /// Writes the event to the file and waits for it to reach the disk, returning the ID to acknowledge it with.
+    pub(crate) fn append(&self, route: Option<String>, event: Event) -> io::Result<u64> {
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        let entry = Entry::Event { id, route, event };
+        write_entry(&mut inner.file, &entry)?;
+        inner.next_id += 1;
+        if let Entry::Event { route, event, .. } = entry {
+            inner.pending.insert(id, (route, event));
+        }
+        Ok(id)
+    }
+
+
+    /// Marks the event as handled so it isn't returned by `pending` again. Acknowledging an event
+    /// twice, or one that isn't in the journal, does nothing.
+    pub fn ack(&self, id: u64) -> io::Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.pending.contains_key(&id) {
+            return Ok(());
+        }
+        write_entry(&mut inner.file, &Entry::Ack { ack: id })?;
+        inner.pending.remove(&id);
+        Ok(())
+    }
+
+
+    /// The events which haven't been acknowledged yet, oldest first.
+    pub fn pending(&self) -> Vec<RoutedEvent> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .pending
+            .iter()
+            .map(|(id, (route, event))| RoutedEvent {
+                id: Some(*id),
+                route: route.clone(),
+                event: event.clone(),
+            })
+            .collect()
+    }
+
+
+    /// Rewrites the file with only the events that haven't been acknowledged, so it doesn't keep growing.
+    /// The new file is written next to the old one and then moved over it, so a crash part way through
+    /// leaves the old file intact.
+    pub fn compact(&self) -> io::Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        let mut tmp_path = inner.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let mut tmp = File::create(&tmp_path)?;
+        for (id, (route, event)) in &inner.pending {
+            let entry = Entry::Event {
+                id: *id,
+                route: route.clone(),
+                event: event.clone(),
+            };
+            serde_json::to_writer(&mut tmp, &entry)?;
+            tmp.write_all(b"\n")?;
+        }
+        tmp.sync_all()?;
+        fs::rename(&tmp_path, &inner.path)?;
+
+        inner.file = OpenOptions::new().append(true).open(&inner.path)?;
+        Ok(())
+    }
+}
+
+
+fn write_entry(file: &mut File, entry: &Entry) -> io::Result<()> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    file.sync_data()
+}
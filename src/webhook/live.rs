@@ -0,0 +1,104 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+use futures::{SinkExt, StreamExt};
+use futures_util::future;
+use serde::Deserialize;
+use warp::ws::{Message, WebSocket};
+use warp::{Filter, Rejection, Reply};
+
+use super::events::{Broadcast, Events};
+use super::server::RoutedEvent;
+use super::{is_authorized, Event};
+
+
+
+/// A path serving received events live to dashboards and other tools, and the token they connect with.
+pub(super) struct Live {
+    pub(super) path: String,
+    pub(super) token: String,
+}
+
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+
+/// Serves every event received from now on as Server-Sent Events on GET requests to the path. Each
+/// event is sent as the same JSON top.gg sent, named `vote` or `test`.
+pub(super) fn sse(live: Option<Live>, broadcast: Arc<Broadcast<RoutedEvent>>) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    authorized(live).map(move || {
+        let events = broadcast.subscribe_lossy(|routed: RoutedEvent| routed.event).map(|event| {
+            let name = match event {
+                Event::Test(_) => "test",
+                Event::BotVote(_) | Event::GuildVote(_) => "vote",
+            };
+            let data = serde_json::to_string(&event).unwrap_or_default();
+            Ok::<_, Infallible>(warp::sse::Event::default().event(name).data(data))
+        });
+        warp::sse::reply(warp::sse::keep_alive().stream(events))
+    })
+}
+
+
+/// Accepts WebSocket connections on the path and sends every event received from now on to each as a
+/// text message holding the same JSON top.gg sent. Messages from the client are ignored.
+pub(super) fn ws(live: Option<Live>, broadcast: Arc<Broadcast<RoutedEvent>>) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    authorized(live).and(warp::ws()).map(move |ws: warp::ws::Ws| {
+        let events = broadcast.subscribe_lossy(|routed: RoutedEvent| routed.event);
+        ws.on_upgrade(move |socket| send_events(socket, events))
+    })
+}
+
+
+/// Sends the events to the socket until either runs out, which for the socket means the client went away.
+async fn send_events(socket: WebSocket, mut events: Events<Event>) {
+    let (mut sink, mut incoming) = socket.split();
+    let send = async move {
+        while let Some(event) = events.next().await {
+            let data = serde_json::to_string(&event).unwrap_or_default();
+            if sink.send(Message::text(data)).await.is_err() {
+                break;
+            }
+        }
+        let _ = sink.close().await;
+    };
+    // Reading is what notices the client closing and answers its pings.
+    let read = async move {
+        while let Some(Ok(message)) = incoming.next().await {
+            if message.is_close() {
+                break;
+            }
+        }
+    };
+    futures::pin_mut!(send, read);
+    future::select(send, read).await;
+}
+
+
+/// Matches GET requests to the path carrying the token, either as the `Authorization` header (with or
+/// without `Bearer `) or as the `token` query parameter since browsers' `EventSource` can't set headers.
+fn authorized(live: Option<Live>) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let live = Arc::new(live);
+    warp::path::full()
+        .and(warp::get())
+        .and(warp::header::optional::<String>("authorization"))
+        .and(warp::query::<TokenQuery>())
+        .and_then(move |full: warp::path::FullPath, header: Option<String>, query: TokenQuery| {
+            let live = match &*live {
+                Some(live) if live.path == full.as_str().trim_matches('/') => live,
+                _ => return future::err(warp::reject::not_found()),
+            };
+            let token = header
+                .as_deref()
+                .map(|header| header.strip_prefix("Bearer ").unwrap_or(header))
+                .or(query.token.as_deref());
+            if is_authorized(&[&live.token], token) {
+                future::ok(())
+            } else {
+                future::err(warp::reject::custom(super::server::Unauthorized))
+            }
+        })
+        .untuple_one()
+}
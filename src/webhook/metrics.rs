@@ -0,0 +1,87 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+
+
+/// Counts of what happened to the requests received by the webhook server since it started, from
+/// `WebhookHandle::metrics`. Useful for alerting when top.gg's deliveries start failing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WebhookMetrics {
+    /// Events which were queued for the subscribers.
+    pub accepted: u64,
+    /// Requests with a missing or wrong authorization header.
+    pub unauthorized: u64,
+    /// Requests whose body wasn't a webhook.
+    pub malformed: u64,
+    /// Requests from IPs not in the allowlist.
+    pub forbidden: u64,
+    /// Requests refused by the per-IP rate limit.
+    pub rate_limited: u64,
+    /// Repeated deliveries of a vote which were answered but not queued, see `WebhookClient::dedup`.
+    pub duplicate: u64,
+    /// Events which were answered but not queued because a filter left them out, see
+    /// `WebhookClient::filter_events`.
+    pub filtered: u64,
+    /// Webhooks refused with a 503 because a subscriber's queue was full or there were no subscribers.
+    pub rejected: u64,
+    /// Events thrown away because a subscriber's queue was full, see `Overflow`.
+    pub dropped: u64,
+}
+impl WebhookMetrics {
+    /// Formats the counts in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP topgg_webhook_requests_total Requests received by the top.gg webhook server by outcome.\n");
+        out.push_str("# TYPE topgg_webhook_requests_total counter\n");
+        let outcomes = [
+            ("accepted", self.accepted),
+            ("unauthorized", self.unauthorized),
+            ("malformed", self.malformed),
+            ("forbidden", self.forbidden),
+            ("rate_limited", self.rate_limited),
+            ("duplicate", self.duplicate),
+            ("filtered", self.filtered),
+            ("rejected", self.rejected),
+        ];
+        for (outcome, count) in outcomes.iter() {
+            let _ = writeln!(out, "topgg_webhook_requests_total{{outcome=\"{}\"}} {}", outcome, count);
+        }
+        out.push_str("# HELP topgg_webhook_events_dropped_total Events thrown away because a subscriber's queue was full.\n");
+        out.push_str("# TYPE topgg_webhook_events_dropped_total counter\n");
+        let _ = writeln!(out, "topgg_webhook_events_dropped_total {}", self.dropped);
+        out
+    }
+}
+
+
+#[derive(Default)]
+pub(crate) struct Counters {
+    pub(crate) accepted: AtomicU64,
+    pub(crate) unauthorized: AtomicU64,
+    pub(crate) malformed: AtomicU64,
+    pub(crate) forbidden: AtomicU64,
+    pub(crate) rate_limited: AtomicU64,
+    pub(crate) duplicate: AtomicU64,
+    pub(crate) filtered: AtomicU64,
+    pub(crate) rejected: AtomicU64,
+}
+impl Counters {
+    pub(crate) fn increment(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+
+    pub(crate) fn snapshot(&self, dropped: u64) -> WebhookMetrics {
+        WebhookMetrics {
+            accepted: self.accepted.load(Ordering::Relaxed),
+            unauthorized: self.unauthorized.load(Ordering::Relaxed),
+            malformed: self.malformed.load(Ordering::Relaxed),
+            forbidden: self.forbidden.load(Ordering::Relaxed),
+            rate_limited: self.rate_limited.load(Ordering::Relaxed),
+            duplicate: self.duplicate.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            dropped,
+        }
+    }
+}
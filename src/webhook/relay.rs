@@ -0,0 +1,224 @@
+use std::time::Duration;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+
+use super::{vote_value, Event};
+
+
+
+/// Posts each vote to a Discord webhook as an embed, for a public "just voted" feed in your server.
+/// The embed's text is a template where these are replaced:
+/// * `{user}` - a mention of the user who voted
+/// * `{user_id}` - the ID of the user who voted
+/// * `{target}` - a mention of the bot, or the ID of the server, that was voted for
+/// * `{target_id}` - the ID of the bot or server that was voted for
+/// * `{points}` - how many points the vote is worth, 2 on weekends and 1 otherwise
+///
+/// Needs the `relay` feature.
+/// ## Examples
+/// ```
+/// use topgg::webhook::DiscordRelay;
+///
+/// let relay = DiscordRelay::new("https://discord.com/api/webhooks/123/abc")
+///     .title("New vote!")
+///     .description("{user} just voted for {target}, thank you!")
+///     .color(0xff3366);
+///
+/// let (events, _handle) = topgg::WebhookClient::start(3030, auth)?;
+/// relay.run(events).await;
+/// ```
+pub struct DiscordRelay {
+    url: String,
+    client: reqwest::Client,
+    title: String,
+    description: String,
+    color: Option<u32>,
+    username: Option<String>,
+    avatar_url: Option<String>,
+    tests: bool,
+}
+impl DiscordRelay {
+    /// Returns a relay posting to the Discord webhook at `webhook_url`.
+    pub fn new(webhook_url: impl Into<String>) -> DiscordRelay {
+        DiscordRelay {
+            url: webhook_url.into(),
+            client: reqwest::Client::new(),
+            title: "New vote".to_string(),
+            description: "{user} voted for {target}!".to_string(),
+            color: None,
+            username: None,
+            avatar_url: None,
+            tests: false,
+        }
+    }
+
+
+    /// Sets the template for the embed's title, `New vote` by default.
+    pub fn title(mut self, template: impl Into<String>) -> DiscordRelay {
+        self.title = template.into();
+        self
+    }
+
+
+    /// Sets the template for the embed's description, `{user} voted for {target}!` by default.
+    pub fn description(mut self, template: impl Into<String>) -> DiscordRelay {
+        self.description = template.into();
+        self
+    }
+
+
+    /// Sets the color of the embed's side bar, like `0xff3366`.
+    pub fn color(mut self, color: u32) -> DiscordRelay {
+        self.color = Some(color);
+        self
+    }
+
+
+    /// Posts as `username` instead of the name set on the Discord webhook.
+    pub fn username(mut self, username: impl Into<String>) -> DiscordRelay {
+        self.username = Some(username.into());
+        self
+    }
+
+
+    /// Posts with the avatar at `url` instead of the one set on the Discord webhook.
+    pub fn avatar_url(mut self, url: impl Into<String>) -> DiscordRelay {
+        self.avatar_url = Some(url.into());
+        self
+    }
+
+
+    /// Also posts test webhooks sent from top.gg, which are left out by default. Useful for checking the
+    /// relay is set up.
+    pub fn include_tests(mut self, include: bool) -> DiscordRelay {
+        self.tests = include;
+        self
+    }
+
+
+    /// Posts the event to Discord. Returns `Ok(false)` if it was left out because it's a test. If
+    /// Discord is rate limiting the webhook the post is tried again once after waiting.
+    pub async fn relay(&self, event: &Event) -> Result<bool, RelayError> {
+        let (user, target, target_mention, points) = match event {
+            Event::BotVote(vote) => (vote.user, vote.bot, format!("<@{}>", vote.bot), vote.vote_value()),
+            Event::GuildVote(vote) => (vote.user, vote.guild, vote.guild.to_string(), vote_value(false)),
+            Event::Test(_) if !self.tests => return Ok(false),
+            Event::Test(vote) => {
+                let target = match vote {
+                    super::Vote::Bot(vote) => vote.bot,
+                    super::Vote::Guild(vote) => vote.guild,
+                };
+                (vote.user(), target, target.to_string(), vote_value(false))
+            }
+        };
+        let fill = |template: &str| {
+            template
+                .replace("{user_id}", &user.to_string())
+                .replace("{user}", &format!("<@{}>", user))
+                .replace("{target_id}", &target.to_string())
+                .replace("{target}", &target_mention)
+                .replace("{points}", &points.to_string())
+        };
+        let message = Message {
+            username: self.username.as_deref(),
+            avatar_url: self.avatar_url.as_deref(),
+            embeds: [Embed {
+                title: fill(&self.title),
+                description: fill(&self.description),
+                color: self.color,
+            }],
+            // Mentions in embeds don't ping anyone, but this keeps it that way if the template changes.
+            allowed_mentions: AllowedMentions { parse: [] },
+        };
+
+        let mut res = self.client.post(&self.url).json(&message).send().await?;
+        if res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = res
+                .headers()
+                .get("retry-after")
+                .and_then(|value| value.to_str().ok()?.parse::<f64>().ok())
+                .filter(|secs| secs.is_finite())
+                .unwrap_or(1.0);
+            crate::rt::sleep(Duration::from_secs_f64(retry_after.clamp(0.0, 60.0))).await;
+            res = self.client.post(&self.url).json(&message).send().await?;
+        }
+        let status = res.status();
+        if !status.is_success() {
+            return Err(RelayError::Discord {
+                status: status.as_u16(),
+                message: res.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(true)
+    }
+
+
+    /// Posts every event from `events` until it ends. Failed posts are logged with the `tracing`
+    /// feature and otherwise skipped.
+    pub async fn run(&self, events: impl Stream<Item = Event>) {
+        futures::pin_mut!(events);
+        while let Some(event) = events.next().await {
+            if let Err(e) = self.relay(&event).await {
+                event!(tracing::Level::WARN, error = %e, "relaying a vote to discord failed");
+            }
+        }
+    }
+}
+
+
+#[derive(Serialize)]
+struct Message<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    avatar_url: Option<&'a str>,
+    embeds: [Embed; 1],
+    allowed_mentions: AllowedMentions,
+}
+
+#[derive(Serialize)]
+struct Embed {
+    title: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct AllowedMentions {
+    parse: [&'static str; 0],
+}
+
+
+/// An error from posting a vote to Discord.
+#[derive(Debug)]
+pub enum RelayError {
+    /// The request failed to send or its response couldn't be read.
+    Http(reqwest::Error),
+    /// Discord responded with an error, such as the webhook having been deleted.
+    Discord {
+        status: u16,
+        message: String,
+    },
+}
+impl std::fmt::Display for RelayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelayError::Http(e) => write!(f, "http error: {}", e),
+            RelayError::Discord { status, message } => write!(f, "discord responded with {}: {}", status, message),
+        }
+    }
+}
+impl std::error::Error for RelayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RelayError::Http(e) => Some(e),
+            RelayError::Discord { .. } => None,
+        }
+    }
+}
+impl From<reqwest::Error> for RelayError {
+    fn from(e: reqwest::Error) -> RelayError {
+        RelayError::Http(e)
+    }
+}
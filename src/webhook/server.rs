@@ -0,0 +1,1073 @@
+use std::net::{IpAddr, SocketAddr};
+#[cfg(feature = "tls")]
+use std::path::PathBuf;
+use std::convert::Infallible;
+use std::error::Error;
+use std::sync::Arc;
+use futures_util::future;
+use std::time::Duration;
+use governor::{Quota, RateLimiter};
+use ipnet::IpNet;
+use serde::Serialize;
+use warp::{Filter, Rejection, Reply};
+use warp::http::StatusCode;
+use warp::hyper::body::Bytes;
+use futures::channel::oneshot;
+use futures::future::BoxFuture;
+use futures::{Future, StreamExt};
+use tokio::task;
+
+use super::ack::{AckQueue, DeadLetters};
+use super::dedup::{self, Dedup};
+use super::events::{Broadcast, Events, Overflow, SendError};
+use super::journal::Journal;
+use super::live::{self, Live};
+use super::metrics::{Counters, WebhookMetrics};
+use super::{is_authorized, Event, DEFAULT_QUEUE_CAPACITY};
+
+
+
+/// The largest body read by default, much bigger than any webhook top.gg sends.
+const DEFAULT_BODY_LIMIT: u64 = 16 * 1024;
+
+/// The longest wait between a `try_on_vote` handler's attempts, however many there are.
+const MAX_HANDLER_BACKOFF: Duration = Duration::from_secs(60);
+
+
+/// Configures and starts the webhook server. `WebhookClient::start` can be used as a shortcut when the defaults are fine.
+pub struct WebhookClient {
+    addr: SocketAddr,
+    auth: Vec<String>,
+    path: Option<String>,
+    routes: Vec<Route>,
+    allowed_ips: Option<Vec<IpNet>>,
+    rate_limit: Option<Quota>,
+    body_limit: u64,
+    metrics_path: Option<String>,
+    sse: Option<Live>,
+    ws: Option<Live>,
+    capacity: Option<usize>,
+    overflow: Overflow,
+    journal: Option<Journal>,
+    dedup: Option<Duration>,
+    handlers: Vec<Handler>,
+    try_handlers: Vec<TryHandler>,
+    handler_retries: (u32, Duration),
+    on_handler_error: Option<ErrorHandler>,
+    hooks: Vec<Hook>,
+    #[cfg(feature = "tls")]
+    tls: Option<Tls>,
+}
+impl WebhookClient {
+    /// Returns a new webhook server config which listens on all interfaces at the given port.
+    /// Port 0 can be used to let the OS pick a free port, see `WebhookHandle::local_addr`.
+    ///
+    /// ## Arguments
+    /// * `port` - The port to listen on
+    /// * `auth` - The authorization string set on your top.gg webhook page
+    ///
+    /// ## Examples
+    /// ```
+    /// let (events, handle) = topgg::WebhookClient::new(3030, auth)
+    ///     .ip([127, 0, 0, 1])
+    ///     .listen()?;
+    /// ```
+    pub fn new(port: u16, auth: String) -> WebhookClient {
+        WebhookClient {
+            addr: SocketAddr::from(([0, 0, 0, 0], port)),
+            auth: vec![auth],
+            path: None,
+            routes: Vec::new(),
+            allowed_ips: None,
+            rate_limit: None,
+            body_limit: DEFAULT_BODY_LIMIT,
+            metrics_path: None,
+            sse: None,
+            ws: None,
+            capacity: Some(DEFAULT_QUEUE_CAPACITY),
+            overflow: Overflow::DropOldest,
+            journal: None,
+            dedup: None,
+            handlers: Vec::new(),
+            try_handlers: Vec::new(),
+            handler_retries: (3, Duration::from_secs(1)),
+            on_handler_error: None,
+            hooks: Vec::new(),
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+
+    /// Starts listening to a port and filtering requests with a authentication string.
+    /// Along with the events a `WebhookHandle` is returned which can be used to stop the server.
+    /// ## Examples
+    /// ```rust
+    /// use futures::StreamExt;
+    /// 
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let (mut events, _handle) = topgg::WebhookClient::start(3030, "a-very-secret-password".to_string())
+    ///         .expect("couldn't listen on port 3030");
+    ///     
+    ///     while let Some(msg) = events.next().await {
+    ///         println!("{:?}", msg)
+    ///     }
+    /// }
+    /// ```
+    pub fn start(port: u16, auth: String) -> Result<(Events<Event>, WebhookHandle), warp::Error> {
+        WebhookClient::new(port, auth).listen()
+    }
+
+
+    /// Sets the IP address to listen on while keeping the port, for example `127.0.0.1` when running
+    /// behind a reverse proxy or `::` to accept IPv6 connections.
+    pub fn ip(mut self, ip: impl Into<IpAddr>) -> WebhookClient {
+        self.addr.set_ip(ip.into());
+        self
+    }
+
+
+    /// Sets the full address to listen on.
+    /// ## Examples
+    /// ```
+    /// use std::net::Ipv6Addr;
+    ///
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .bind((Ipv6Addr::LOCALHOST, 8080));
+    /// ```
+    pub fn bind(mut self, addr: impl Into<SocketAddr>) -> WebhookClient {
+        self.addr = addr.into();
+        self
+    }
+
+
+    /// Also accepts webhooks with this authorization string. While changing the secret on top.gg both
+    /// the old and new one can be accepted so no votes are refused during the changeover.
+    /// ## Examples
+    /// ```
+    /// let webhook = topgg::WebhookClient::new(3030, new_auth)
+    ///     .accept_auth(old_auth);
+    /// ```
+    pub fn accept_auth(mut self, auth: String) -> WebhookClient {
+        self.auth.push(auth);
+        self
+    }
+
+
+    /// Only accepts webhooks sent to this path, for example `/topgg/webhook`. Requests to any other
+    /// path are rejected with a 404. By default webhooks are accepted on every path.
+    /// ## Examples
+    /// ```
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .path("/topgg/webhook");
+    /// ```
+    pub fn path(mut self, path: &str) -> WebhookClient {
+        self.path = Some(path.trim_matches('/').to_string());
+        self
+    }
+
+
+    /// Adds a route for another bot on the same server, accepting webhooks sent to `path` with the
+    /// authorization string `auth`. Events from it are tagged with `name`, which can be read from the
+    /// stream returned by `WebhookHandle::subscribe_routed`. Routes are checked in the order they were
+    /// added, before the path and authorization given to `new`.
+    /// ## Examples
+    /// ```
+    /// let (_, handle) = topgg::WebhookClient::new(3030, main_auth)
+    ///     .path("/main")
+    ///     .route("music", "/music", music_auth)
+    ///     .listen()?;
+    /// let mut events = handle.subscribe_routed();
+    /// while let Some(routed) = events.next().await {
+    ///     println!("{:?} got {:?}", routed.route, routed.event);
+    /// }
+    /// ```
+    pub fn route(mut self, name: &str, path: &str, auth: String) -> WebhookClient {
+        self.routes.push(Route {
+            name: Some(name.to_string()),
+            path: Some(path.trim_matches('/').to_string()),
+            auth: vec![auth],
+        });
+        self
+    }
+
+
+    /// Only accepts requests coming from these IP ranges, such as top.gg's or your reverse proxy's,
+    /// rejecting any others before their body is read. Every IP is allowed by default.
+    /// ## Examples
+    /// ```
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .allow_ips(vec!["159.203.105.187/32".parse().unwrap()]);
+    /// ```
+    pub fn allow_ips(mut self, ranges: impl IntoIterator<Item = IpNet>) -> WebhookClient {
+        self.allowed_ips
+            .get_or_insert_with(Vec::new)
+            .extend(ranges);
+        self
+    }
+
+
+    /// Limits how often each IP can send requests, rejecting any over the limit with a 429. Legitimate
+    /// webhooks are rare, so this mainly stops junk traffic from flooding the server.
+    /// ## Examples
+    /// ```
+    /// use std::num::NonZeroU32;
+    /// use topgg::webhook::Quota;
+    ///
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .rate_limit(Quota::per_second(NonZeroU32::new(5).unwrap()));
+    /// ```
+    pub fn rate_limit(mut self, quota: Quota) -> WebhookClient {
+        self.rate_limit = Some(quota);
+        self
+    }
+
+
+    /// Sets the largest request body, in bytes, that will be read. Webhooks from top.gg are tiny, so
+    /// by default anything over 16 KiB is rejected with a 413.
+    pub fn body_limit(mut self, bytes: u64) -> WebhookClient {
+        self.body_limit = bytes;
+        self
+    }
+
+
+    /// Serves the server's metrics in the Prometheus text format on GET requests to this path. The
+    /// route isn't behind the authorization or IP allowlist, so only expose it where it's safe to.
+    /// The same numbers are always available from `WebhookHandle::metrics`.
+    /// ## Examples
+    /// ```
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .metrics_path("/metrics");
+    /// ```
+    pub fn metrics_path(mut self, path: &str) -> WebhookClient {
+        self.metrics_path = Some(path.trim_matches('/').to_string());
+        self
+    }
+
+
+    /// Streams every event received to GET requests on this path as Server-Sent Events, for showing
+    /// votes live on a dashboard. Each event is the same JSON top.gg sent, named `vote` or `test`.
+    /// Requests have to give `token` as the `Authorization` header or, since a browser's `EventSource`
+    /// can't set headers, as the `token` query parameter. The IP allowlist and rate limit don't apply to
+    /// this path, and a client reading too slowly misses the oldest events rather than holding up webhooks.
+    /// ## Examples
+    /// ```
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .path("/topgg/webhook")
+    ///     .sse("/events", dashboard_token);
+    /// // new EventSource("/events?token=...") in the browser
+    /// ```
+    pub fn sse(mut self, path: &str, token: String) -> WebhookClient {
+        self.sse = Some(Live {
+            path: path.trim_matches('/').to_string(),
+            token,
+        });
+        self
+    }
+
+
+    /// Accepts WebSocket connections on this path and sends every event received to each as a text
+    /// message with the same JSON top.gg sent, so internal tools in any language can follow votes
+    /// live. The token is checked the same way as for `sse`, and the same caveats apply.
+    /// ## Examples
+    /// ```
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .path("/topgg/webhook")
+    ///     .ws("/ws", tools_token);
+    /// // new WebSocket("wss://bot.example.com/ws?token=...")
+    /// ```
+    pub fn ws(mut self, path: &str, token: String) -> WebhookClient {
+        self.ws = Some(Live {
+            path: path.trim_matches('/').to_string(),
+            token,
+        });
+        self
+    }
+
+
+    /// Sets how many unread events can be queued up for each subscriber and what to do with webhooks
+    /// that arrive once a queue is full. By default 1024 events are queued for each subscriber and
+    /// a full queue loses its oldest event, so a subscriber that stops reading doesn't hold up the rest.
+    /// ## Examples
+    /// ```
+    /// use topgg::webhook::Overflow;
+    ///
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .queue(Some(100), Overflow::DropOldest);
+    /// ```
+    pub fn queue(mut self, capacity: Option<usize>, overflow: Overflow) -> WebhookClient {
+        self.capacity = capacity;
+        self.overflow = overflow;
+        self
+    }
+
+
+    /// Writes every event to the journal before responding to top.gg, so votes survive a crash until
+    /// they're acknowledged. If an event can't be written the webhook is refused with a 503 so top.gg
+    /// retries it. See `Journal` for how events are acknowledged and read back after a restart.
+    /// ## Examples
+    /// ```
+    /// let journal = topgg::webhook::Journal::open("votes.jsonl")?;
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .journal(journal.clone());
+    /// ```
+    pub fn journal(mut self, journal: Journal) -> WebhookClient {
+        self.journal = Some(journal);
+        self
+    }
+
+
+    /// Only passes on the first of several deliveries of the same vote (the same user voting for the
+    /// same bot or server) within `window`, such as when top.gg retries a delivery it thinks failed.
+    /// Repeats are still answered with a 200 so top.gg stops retrying. Test webhooks always go through.
+    /// ## Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .dedup(Duration::from_secs(60 * 60));
+    /// ```
+    pub fn dedup(mut self, window: Duration) -> WebhookClient {
+        self.dedup = Some(window);
+        self
+    }
+
+
+    /// Only passes on events for which `keep` returns true. Others are still answered with a 200 so
+    /// top.gg doesn't retry them, and are counted in `WebhookMetrics::filtered`. Filters and maps run
+    /// in the order they were added, before the event is deduplicated, journaled or queued.
+    /// ## Examples
+    /// ```
+    /// use topgg::webhook::Event;
+    ///
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .filter_events(|event| !matches!(event, Event::Test(_)))
+    ///     .filter_events(move |event| match event {
+    ///         Event::BotVote(vote) => vote.bot == my_bot_id,
+    ///         _ => true,
+    ///     });
+    /// ```
+    pub fn filter_events<F>(mut self, keep: F) -> WebhookClient
+    where
+        F: Fn(&Event) -> bool + Send + Sync + 'static,
+    {
+        self.hooks.push(Hook::Filter(Box::new(keep)));
+        self
+    }
+
+
+    /// Replaces each event with what `map` returns before it's passed on, such as to clear fields the
+    /// consumers shouldn't see. See `filter_events` for when it runs.
+    /// ## Examples
+    /// ```
+    /// use topgg::webhook::Event;
+    ///
+    /// let webhook = topgg::WebhookClient::new(3030, auth)
+    ///     .map_events(|mut event| {
+    ///         if let Event::BotVote(vote) = &mut event {
+    ///             vote.query = None;
+    ///         }
+    ///         event
+    ///     });
+    /// ```
+    pub fn map_events<F>(mut self, map: F) -> WebhookClient
+    where
+        F: Fn(Event) -> Event + Send + Sync + 'static,
+    {
+        self.hooks.push(Hook::Map(Box::new(map)));
+        self
+    }
+
+
+    /// Runs the handler for every vote received, so a consumer loop isn't needed. Each vote is handled
+    /// in its own task, and several handlers can be added.
+    /// ## Examples
+    /// ```
+    /// let (_, handle) = topgg::WebhookClient::new(3030, auth)
+    ///     .on_vote(|event| async move {
+    ///         println!("{:?}", event);
+    ///     })
+    ///     .listen()?;
+    /// ```
+    pub fn on_vote<F, Fut>(mut self, handler: F) -> WebhookClient
+    where
+        F: Fn(Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.handlers.push(Arc::new(move |vote| Box::pin(handler(vote))));
+        self
+    }
+
+
+    /// Like `on_vote`, but the handler is also given a clone of `state`, such as a database pool or
+    /// a client for sending messages.
+    /// ## Examples
+    /// ```
+    /// let (_, handle) = topgg::WebhookClient::new(3030, auth)
+    ///     .on_vote_with(db.clone(), |db, event| async move {
+    ///         if let topgg::webhook::Event::BotVote(vote) = event {
+    ///             db.add_reward(vote.user).await;
+    ///         }
+    ///     })
+    ///     .listen()?;
+    /// ```
+    pub fn on_vote_with<S, F, Fut>(self, state: S, handler: F) -> WebhookClient
+    where
+        S: Clone + Send + Sync + 'static,
+        F: Fn(S, Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_vote(move |vote| handler(state.clone(), vote))
+    }
+
+
+    /// Like `on_vote`, but the handler can fail, in which case it's run again after a backoff. See
+    /// `handler_retries` for how often, and `on_handler_error` for finding out about votes it gave up on.
+    /// ## Examples
+    /// ```
+    /// let (_, handle) = topgg::WebhookClient::new(3030, auth)
+    ///     .try_on_vote(move |event| {
+    ///         let db = db.clone();
+    ///         async move { db.add_reward(event.user()).await }
+    ///     })
+    ///     .handler_retries(5, Duration::from_millis(500))
+    ///     .on_handler_error(|event, error| eprintln!("couldn't reward {}: {}", event.user(), error))
+    ///     .listen()?;
+    /// ```
+    pub fn try_on_vote<F, Fut, E>(mut self, handler: F) -> WebhookClient
+    where
+        F: Fn(Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Into<Box<dyn Error + Send + Sync>> + 'static,
+    {
+        let handler = Arc::new(handler);
+        self.try_handlers.push(Arc::new(move |vote| -> BoxFuture<'static, Result<(), Box<dyn Error + Send + Sync>>> {
+            let handler = handler.clone();
+            Box::pin(async move { handler(vote).await.map_err(Into::into) })
+        }));
+        self
+    }
+
+
+    /// Runs a failing `try_on_vote` handler at most `max_attempts` times for each vote, waiting
+    /// `backoff` before the first retry and twice as long before each one after, up to a minute.
+    /// It's 3 attempts from a second by default.
+    pub fn handler_retries(mut self, max_attempts: u32, backoff: Duration) -> WebhookClient {
+        self.handler_retries = (max_attempts.max(1), backoff);
+        self
+    }
+
+
+    /// Calls `callback` with the vote and the last error when a `try_on_vote` handler has failed every
+    /// attempt, such as to store the vote for handling by hand.
+    pub fn on_handler_error<F>(mut self, callback: F) -> WebhookClient
+    where
+        F: Fn(Event, Box<dyn Error + Send + Sync>) + Send + Sync + 'static,
+    {
+        self.on_handler_error = Some(Arc::new(callback));
+        self
+    }
+
+
+    /// Serves the webhook over HTTPS using the PEM encoded certificate and private key at the given paths,
+    /// for when there's no reverse proxy in front of the server to terminate TLS. Needs the `tls` feature.
+    /// ## Examples
+    /// ```
+    /// let webhook = topgg::WebhookClient::new(443, auth)
+    ///     .tls("/etc/ssl/certs/bot.pem", "/etc/ssl/private/bot.key");
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> WebhookClient {
+        self.tls = Some(Tls {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+        });
+        self
+    }
+
+
+    /// Starts the server with this config.
+    /// Along with the events a `WebhookHandle` is returned which can be used to stop the server.
+    /// Fails if the address can't be bound, such as when another process is using the port.
+    pub fn listen(mut self) -> Result<(Events<Event>, WebhookHandle), warp::Error> {
+        let (webhook, broadcast, counters) = self.build();
+        let webhook = webhook.recover(recover_all);
+        let addr = self.addr;
+        let event_read = broadcast.subscribe_map(|routed: RoutedEvent| routed.event);
+
+        let (shutdown_send, shutdown_read) = oneshot::channel::<()>();
+        let signal = async move {
+            // Dropping the handle without calling shutdown leaves the server running.
+            if shutdown_read.await.is_err() {
+                future::pending::<()>().await;
+            }
+        };
+        #[cfg(feature = "tls")]
+        let (addr, server) = match self.tls {
+            Some(tls) => {
+                let (addr, server) = warp::serve(webhook)
+                    .tls()
+                    .cert_path(tls.cert_path)
+                    .key_path(tls.key_path)
+                    .try_bind_with_graceful_shutdown(addr, signal)?;
+                (addr, task::spawn(server))
+            }
+            None => {
+                let (addr, server) = warp::serve(webhook).try_bind_with_graceful_shutdown(addr, signal)?;
+                (addr, task::spawn(server))
+            }
+        };
+        #[cfg(not(feature = "tls"))]
+        let (addr, server) = {
+            let (addr, server) = warp::serve(webhook).try_bind_with_graceful_shutdown(addr, signal)?;
+            (addr, task::spawn(server))
+        };
+
+        let handle = WebhookHandle {
+            addr,
+            events: broadcast,
+            counters,
+            shutdown: shutdown_send,
+            server,
+        };
+        Ok((event_read, handle))
+    }
+
+
+    /// Turns this config into a warp filter which can be mounted in your own warp server instead of
+    /// starting a new one. The address and TLS settings are ignored since your server handles those.
+    /// Refused webhooks are left as rejections so other routes can still match; `webhook::recover`
+    /// turns them into the same responses the built in server sends.
+    /// ## Examples
+    /// ```
+    /// use warp::{Filter, Rejection, Reply};
+    ///
+    /// let (webhook, events) = topgg::WebhookClient::new(0, auth)
+    ///     .path("/topgg/webhook")
+    ///     .into_filter();
+    /// let routes = webhook.or(dashboard_routes).recover(topgg::webhook::recover);
+    /// warp::serve(routes).run(([0, 0, 0, 0], 8080)).await;
+    /// ```
+    pub fn into_filter(mut self) -> (impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone, Events<Event>) {
+        let (webhook, broadcast, _) = self.build();
+        (webhook, broadcast.subscribe_map(|routed: RoutedEvent| routed.event))
+    }
+
+
+    /// Builds the filter, taking the settings it needs out of the config.
+    fn build(&mut self) -> (impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone, Arc<Broadcast<RoutedEvent>>, Arc<Counters>) {
+        let counters = Arc::new(Counters::default());
+        let broadcast = Arc::new(Broadcast::new(self.capacity, self.overflow));
+
+        let mut routes = std::mem::take(&mut self.routes);
+        routes.push(Route {
+            name: None,
+            path: self.path.take(),
+            auth: std::mem::take(&mut self.auth),
+        });
+        let routes = Arc::new(routes);
+
+        let known = routes.clone();
+        let route = warp::path::full()
+            .and_then(move |full: warp::path::FullPath| {
+                let path = full.as_str().trim_matches('/');
+                if known.iter().any(|route| route.matches(path)) {
+                    future::ok(())
+                } else {
+                    future::err(warp::reject::not_found())
+                }
+            })
+            .untuple_one();
+
+        let allowed_ips = self.allowed_ips.take();
+        let counted = counters.clone();
+        let source = warp::addr::remote()
+            .and_then(move |remote: Option<SocketAddr>| {
+                let allowed = match &allowed_ips {
+                    Some(ranges) => remote.map_or(false, |remote| {
+                        let ip = canonical_ip(remote.ip());
+                        ranges.iter().any(|range| range.contains(&ip))
+                    }),
+                    None => true,
+                };
+                if allowed {
+                    future::ok(())
+                } else {
+                    Counters::increment(&counted.forbidden);
+                    future::err(warp::reject::custom(Forbidden))
+                }
+            })
+            .untuple_one();
+
+        let limiter = self.rate_limit.map(|quota| {
+            let limiter = Arc::new(RateLimiter::keyed(quota));
+            // Forget about IPs that haven't sent anything recently so scanners can't grow the map forever.
+            let weak = Arc::downgrade(&limiter);
+            task::spawn(async move {
+                loop {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    match weak.upgrade() {
+                        Some(limiter) => limiter.retain_recent(),
+                        None => break,
+                    }
+                }
+            });
+            limiter
+        });
+        let counted = counters.clone();
+        let throttle = warp::addr::remote()
+            .and_then(move |remote: Option<SocketAddr>| {
+                let allowed = match (&limiter, remote) {
+                    (Some(limiter), Some(remote)) => limiter.check_key(&canonical_ip(remote.ip())).is_ok(),
+                    _ => true,
+                };
+                if allowed {
+                    future::ok(())
+                } else {
+                    Counters::increment(&counted.rate_limited);
+                    future::err(warp::reject::custom(TooManyRequests))
+                }
+            })
+            .untuple_one();
+
+        let counted = counters.clone();
+        let filter = warp::path::full()
+            .and(warp::header::optional::<String>("authorization"))
+            .and_then(move |full: warp::path::FullPath, value: Option<String>| {
+                let path = full.as_str().trim_matches('/');
+                let found = routes
+                    .iter()
+                    .find(|route| route.matches(path) && is_authorized(&route.auth[..], value.as_deref()));
+                match found {
+                    Some(route) => future::ok(route.name.clone()),
+                    None => {
+                        Counters::increment(&counted.unauthorized);
+                        future::err(warp::reject::custom(Unauthorized))
+                    }
+                }
+            });
+
+        let counted = counters.clone();
+        let body = warp::body::content_length_limit(self.body_limit)
+            .and(warp::body::bytes())
+            .and_then(move |body: Bytes| match serde_json::from_slice::<Event>(&body) {
+                Ok(event) => future::ok(event),
+                Err(_) => {
+                    Counters::increment(&counted.malformed);
+                    future::err(warp::reject::custom(Malformed))
+                }
+            });
+
+        let sender = broadcast.clone();
+        let counted = counters.clone();
+
+        let retries = self.handler_retries;
+        for handler in self.try_handlers.drain(..) {
+            let on_error = self.on_handler_error.clone();
+            self.handlers.push(Arc::new(move |vote| {
+                Box::pin(run_with_retries(handler.clone(), vote, retries, on_error.clone()))
+            }));
+        }
+        for handler in self.handlers.drain(..) {
+            let mut votes = broadcast.subscribe_map(|routed: RoutedEvent| routed.event);
+            task::spawn(async move {
+                while let Some(vote) = votes.next().await {
+                    task::spawn(handler(vote));
+                }
+            });
+        }
+
+        let journal = self.journal.take();
+        let hooks = Arc::new(std::mem::take(&mut self.hooks));
+        let dedup = self.dedup.map(|window| Arc::new(Dedup::new(window)));
+
+        let webhook = route
+            .and(warp::post())
+            .and(source)
+            .and(throttle)
+            .and(filter)
+            .and(body)
+            .and_then(move |route: Option<String>, event: Event| {
+                let hooks = hooks.clone();
+                let dedup = dedup.clone();
+                let journal = journal.clone();
+                let sender = sender.clone();
+                let counted = counted.clone();
+                async move {
+                    let event = match apply_hooks(&hooks, event) {
+                        Some(event) => event,
+                        None => {
+                            Counters::increment(&counted.filtered);
+                            return respond(StatusCode::OK);
+                        }
+                    };
+                    let key = dedup.as_ref().and_then(|_| dedup::key(&event));
+                    if let (Some(dedup), Some(key)) = (&dedup, key) {
+                        if !dedup.first_seen(key) {
+                            Counters::increment(&counted.duplicate);
+                            return respond(StatusCode::OK);
+                        }
+                    }
+                    // A vote that isn't queued will be delivered again, so it mustn't be taken for a repeat then.
+                    let forget = || {
+                        if let (Some(dedup), Some(key)) = (&dedup, key) {
+                            dedup.forget(key);
+                        }
+                    };
+
+                    // Writing waits for the disk, so it's done off the runtime's threads.
+                    let id = match journal.clone() {
+                        Some(journal) => {
+                            let (route, event) = (route.clone(), event.clone());
+                            match task::spawn_blocking(move || journal.append(route, event)).await {
+                                Ok(Ok(id)) => Some(id),
+                                _ => {
+                                    forget();
+                                    Counters::increment(&counted.rejected);
+                                    return respond(StatusCode::SERVICE_UNAVAILABLE);
+                                }
+                            }
+                        }
+                        None => None,
+                    };
+                    match sender.send(RoutedEvent { id, route, event }) {
+                        Ok(()) => {
+                            Counters::increment(&counted.accepted);
+                            respond(StatusCode::OK)
+                        }
+                        Err(SendError::Full) | Err(SendError::Closed) => {
+                            // top.gg will deliver it again, so it shouldn't be replayed from the journal as well.
+                            if let (Some(journal), Some(id)) = (journal, id) {
+                                let _ = task::spawn_blocking(move || journal.ack(id)).await;
+                            }
+                            forget();
+                            Counters::increment(&counted.rejected);
+                            respond(StatusCode::SERVICE_UNAVAILABLE)
+                        }
+                    }
+                }
+            });
+
+        let metrics_path = self.metrics_path.take();
+        let metrics_source = broadcast.clone();
+        let metrics_counters = counters.clone();
+        let metrics = warp::path::full()
+            .and_then(move |full: warp::path::FullPath| {
+                let matches = metrics_path.as_deref() == Some(full.as_str().trim_matches('/'));
+                if matches {
+                    future::ok(())
+                } else {
+                    future::err(warp::reject::not_found())
+                }
+            })
+            .untuple_one()
+            .and(warp::get())
+            .map(move || {
+                let metrics = metrics_counters.snapshot(metrics_source.dropped());
+                warp::reply::with_header(metrics.to_prometheus(), "content-type", "text/plain; version=0.0.4")
+            });
+
+        let sse = live::sse(self.sse.take(), broadcast.clone());
+        let ws = live::ws(self.ws.take(), broadcast.clone());
+
+        (metrics.or(sse).or(ws).or(webhook), broadcast, counters)
+    }
+}
+
+
+/// A shortcut for `WebhookClient::into_filter` with the default settings, for mounting the webhook in your own warp server.
+/// ## Examples
+/// ```
+/// let (webhook, events) = topgg::webhook::filter(auth);
+/// warp::serve(webhook.or(dashboard_routes)).run(([0, 0, 0, 0], 8080)).await;
+/// ```
+pub fn filter(auth: String) -> (impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone, Events<Event>) {
+    WebhookClient::new(0, auth).into_filter()
+}
+
+
+/// Turns the rejections from a refused webhook into responses with the right status code and a
+/// small JSON body saying what went wrong, for use with `Filter::recover`. Other rejections are
+/// passed on to be handled as usual.
+/// ## Examples
+/// ```
+/// let (webhook, events) = topgg::webhook::filter(auth);
+/// let routes = webhook.or(dashboard_routes).recover(topgg::webhook::recover);
+/// ```
+pub async fn recover(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    match webhook_error(&rejection) {
+        Some((status, message)) => Ok(error_reply(status, message)),
+        None => Err(rejection),
+    }
+}
+
+
+async fn recover_all(rejection: Rejection) -> Result<impl Reply, Infallible> {
+    let (status, message) = webhook_error(&rejection).unwrap_or_else(|| {
+        if rejection.is_not_found() {
+            (StatusCode::NOT_FOUND, "not found")
+        } else if rejection.find::<warp::reject::MethodNotAllowed>().is_some() {
+            (StatusCode::METHOD_NOT_ALLOWED, "method not allowed")
+        } else {
+            (StatusCode::INTERNAL_SERVER_ERROR, "internal server error")
+        }
+    });
+    Ok(error_reply(status, message))
+}
+
+
+fn webhook_error(rejection: &Rejection) -> Option<(StatusCode, &'static str)> {
+    if rejection.find::<Unauthorized>().is_some() {
+        Some((StatusCode::UNAUTHORIZED, "unauthorized"))
+    } else if rejection.find::<Forbidden>().is_some() {
+        Some((StatusCode::FORBIDDEN, "forbidden"))
+    } else if rejection.find::<TooManyRequests>().is_some() {
+        Some((StatusCode::TOO_MANY_REQUESTS, "too many requests"))
+    } else if rejection.find::<Malformed>().is_some() {
+        Some((StatusCode::BAD_REQUEST, "malformed webhook body"))
+    } else if rejection.find::<warp::reject::PayloadTooLarge>().is_some() {
+        Some((StatusCode::PAYLOAD_TOO_LARGE, "body too large"))
+    } else if rejection.find::<warp::reject::LengthRequired>().is_some() {
+        Some((StatusCode::LENGTH_REQUIRED, "content-length required"))
+    } else {
+        None
+    }
+}
+
+
+fn respond(status: StatusCode) -> Result<impl Reply, Rejection> {
+    Ok(warp::reply::with_status(warp::reply(), status))
+}
+
+
+fn error_reply(status: StatusCode, message: &str) -> impl Reply {
+    warp::reply::with_status(warp::reply::json(&ErrorBody { error: message }), status)
+}
+
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: &'a str,
+}
+
+
+/// A path and the authorization strings accepted on it, for one of the bots sharing the server.
+struct Route {
+    name: Option<String>,
+    path: Option<String>,
+    auth: Vec<String>,
+}
+impl Route {
+    fn matches(&self, path: &str) -> bool {
+        match &self.path {
+            Some(own) => own == path,
+            None => true,
+        }
+    }
+}
+
+
+/// An event along with the name of the route it was received on, as given to `WebhookClient::route`.
+/// The route is `None` for webhooks received with the path and authorization given to `WebhookClient::new`.
+#[derive(Debug, Clone)]
+pub struct RoutedEvent {
+    /// The ID to acknowledge the event with when a `Journal` is used.
+    pub id: Option<u64>,
+    pub route: Option<String>,
+    pub event: Event,
+}
+
+
+type Handler = Arc<dyn Fn(Event) -> BoxFuture<'static, ()> + Send + Sync>;
+type TryHandler = Arc<dyn Fn(Event) -> BoxFuture<'static, Result<(), Box<dyn Error + Send + Sync>>> + Send + Sync>;
+type ErrorHandler = Arc<dyn Fn(Event, Box<dyn Error + Send + Sync>) + Send + Sync>;
+
+
+/// Runs the handler until it succeeds or has failed `max_attempts` times, doubling the wait each time.
+async fn run_with_retries(handler: TryHandler, vote: Event, (max_attempts, backoff): (u32, Duration), on_error: Option<ErrorHandler>) {
+    let mut delay = backoff;
+    let mut attempt = 1;
+    loop {
+        match handler(vote.clone()).await {
+            Ok(()) => return,
+            Err(e) if attempt >= max_attempts => {
+                event!(tracing::Level::WARN, error = %e, attempts = attempt, "vote handler failed every attempt");
+                if let Some(on_error) = on_error {
+                    on_error(vote, e);
+                }
+                return;
+            }
+            Err(e) => {
+                event!(tracing::Level::DEBUG, error = %e, attempt, "vote handler failed, retrying");
+            }
+        }
+        tokio::time::sleep(delay).await;
+        delay = delay.saturating_mul(2).min(MAX_HANDLER_BACKOFF);
+        attempt += 1;
+    }
+}
+
+
+/// A step events go through before being passed on, from `WebhookClient::filter_events` or `map_events`.
+enum Hook {
+    Filter(Box<dyn Fn(&Event) -> bool + Send + Sync>),
+    Map(Box<dyn Fn(Event) -> Event + Send + Sync>),
+}
+
+
+/// Runs the event through every hook in order, returning `None` if a filter left it out.
+fn apply_hooks(hooks: &[Hook], mut event: Event) -> Option<Event> {
+    for hook in hooks {
+        match hook {
+            Hook::Filter(keep) => {
+                if !keep(&event) {
+                    return None;
+                }
+            }
+            Hook::Map(map) => event = map(event),
+        }
+    }
+    Some(event)
+}
+
+
+#[cfg(feature = "tls")]
+struct Tls {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+}
+
+
+/// A handle to a running webhook server.
+pub struct WebhookHandle {
+    addr: SocketAddr,
+    events: Arc<Broadcast<RoutedEvent>>,
+    counters: Arc<Counters>,
+    shutdown: oneshot::Sender<()>,
+    server: task::JoinHandle<()>,
+}
+impl WebhookHandle {
+    /// The address the server is listening on. When started on port 0 this has the port the OS picked.
+    /// ## Examples
+    /// ```
+    /// let (events, handle) = topgg::WebhookClient::start(0, auth)?;
+    /// println!("listening on {}", handle.local_addr());
+    /// ```
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+
+    /// Returns a new stream of the events received from now on, independent of any other subscriber,
+    /// so that several tasks can each see every vote.
+    /// ## Examples
+    /// ```
+    /// let (rewards, handle) = topgg::WebhookClient::start(3030, auth)?;
+    /// let analytics = handle.subscribe();
+    /// ```
+    pub fn subscribe(&self) -> Events<Event> {
+        self.events.subscribe_map(|routed: RoutedEvent| routed.event)
+    }
+
+
+    /// Like `subscribe`, but each event comes with the name of the route it was received on, for
+    /// telling apart the bots sharing the server.
+    pub fn subscribe_routed(&self) -> Events<RoutedEvent> {
+        self.events.subscribe()
+    }
+
+
+    /// Like `subscribe_routed`, but each event has to be acknowledged once it's handled, and is handed
+    /// out again if it isn't. Events that fail every attempt are moved to `dead_letters`. See `AckQueue`.
+    pub fn subscribe_acked(&self, dead_letters: DeadLetters) -> AckQueue {
+        AckQueue::new(self.subscribe_routed(), dead_letters)
+    }
+
+
+    /// The counts of what happened to the requests received so far.
+    /// ## Examples
+    /// ```
+    /// let metrics = handle.metrics();
+    /// if metrics.malformed > 0 {
+    ///     println!("received {} malformed webhooks", metrics.malformed);
+    /// }
+    /// ```
+    pub fn metrics(&self) -> WebhookMetrics {
+        self.counters.snapshot(self.events.dropped())
+    }
+
+
+    /// Stops the server from accepting new connections and waits for requests that are already
+    /// being handled to finish, after which every `Events` stream ends. Dropping the handle instead
+    /// leaves the server running.
+    /// ## Examples
+    /// ```
+    /// let (events, handle) = topgg::WebhookClient::start(3030, auth)?;
+    /// // ...
+    /// handle.shutdown().await;
+    /// ```
+    pub async fn shutdown(self) {
+        let _ = self.shutdown.send(());
+        let _ = self.server.await;
+        self.events.close();
+    }
+}
+
+
+
+#[derive(Debug)]
+pub(super) struct Unauthorized;
+impl warp::reject::Reject for Unauthorized {}
+impl std::fmt::Display for Unauthorized {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Unauthorized")
+    }
+}
+impl std::error::Error for Unauthorized {}
+
+
+#[derive(Debug)]
+struct Forbidden;
+impl warp::reject::Reject for Forbidden {}
+impl std::fmt::Display for Forbidden {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Forbidden")
+    }
+}
+impl std::error::Error for Forbidden {}
+
+
+#[derive(Debug)]
+struct Malformed;
+impl warp::reject::Reject for Malformed {}
+impl std::fmt::Display for Malformed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Malformed webhook body")
+    }
+}
+impl std::error::Error for Malformed {}
+
+
+#[derive(Debug)]
+struct TooManyRequests;
+impl warp::reject::Reject for TooManyRequests {}
+impl std::fmt::Display for TooManyRequests {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Too Many Requests")
+    }
+}
+impl std::error::Error for TooManyRequests {}
+
+
+/// IPv4 clients connecting to a server listening on IPv6 show up as IPv4-mapped addresses, which
+/// wouldn't match IPv4 ranges.
+fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => IpAddr::V6(v6),
+        },
+        ip => ip,
+    }
+}
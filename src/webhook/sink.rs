@@ -0,0 +1,81 @@
+//! Sending received webhook events on to other services, for when the webhook server is a thin edge in
+//! front of the consumers.
+
+use std::error::Error;
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+
+use super::Event;
+
+#[cfg(feature = "forward")]
+mod http;
+#[cfg(feature = "forward")]
+pub use http::HttpForwarder;
+#[cfg(feature = "redis")]
+mod redis;
+#[cfg(feature = "redis")]
+pub use self::redis::RedisPublisher;
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaSink;
+#[cfg(feature = "nats")]
+mod nats;
+#[cfg(feature = "nats")]
+pub use nats::NatsSink;
+
+
+
+/// Somewhere webhook events are sent on to. Implement this to send them to your own queue or service.
+/// ## Examples
+/// ```
+/// use topgg::webhook::{EventSink, HttpForwarder};
+///
+/// let forwarder = HttpForwarder::new(["http://rewards.internal/votes"]);
+/// let (events, _handle) = topgg::WebhookClient::start(3030, auth)?;
+/// forwarder.run(events).await;
+/// ```
+#[async_trait]
+pub trait EventSink: Send + Sync {
+    /// Sends one event on.
+    async fn send(&self, event: &Event) -> Result<(), SinkError>;
+
+    /// Sends every event from `events` on until it ends. Failures are logged with the `tracing`
+    /// feature and otherwise skipped, so one lost event doesn't stop the rest.
+    async fn run<S: Stream<Item = Event> + Send>(&self, events: S)
+    where
+        Self: Sized,
+    {
+        futures::pin_mut!(events);
+        while let Some(event) = events.next().await {
+            if let Err(e) = self.send(&event).await {
+                event!(tracing::Level::WARN, error = %e, "sending an event on failed");
+            }
+        }
+    }
+}
+
+
+/// An error from an `EventSink`, such as the service it sends to being down.
+#[derive(Debug)]
+pub struct SinkError {
+    source: Box<dyn Error + Send + Sync>,
+}
+impl SinkError {
+    /// Wraps an error from a sink's backend.
+    pub fn new(source: impl Into<Box<dyn Error + Send + Sync>>) -> SinkError {
+        SinkError {
+            source: source.into(),
+        }
+    }
+}
+impl std::fmt::Display for SinkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "event sink failed: {}", self.source)
+    }
+}
+impl Error for SinkError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.source)
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use futures::future;
+
+use super::{EventSink, SinkError};
+use crate::webhook::Event;
+
+
+
+/// The longest wait between retries, however many there are.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+
+/// Re-posts each event to one or more URLs, in the same JSON top.gg sent, so the services behind them
+/// can read it with `topgg::webhook::parse`. Failed posts are retried with exponential backoff. Needs
+/// the `forward` feature.
+/// ## Examples
+/// ```
+/// use topgg::webhook::{EventSink, HttpForwarder};
+///
+/// let forwarder = HttpForwarder::new(["http://rewards.internal/votes", "http://analytics.internal/votes"])
+///     .authorization("internal-secret")
+///     .retries(5, Duration::from_secs(1));
+/// forwarder.run(events).await;
+/// ```
+pub struct HttpForwarder {
+    client: reqwest::Client,
+    urls: Vec<String>,
+    authorization: Option<String>,
+    retries: u32,
+    backoff: Duration,
+}
+impl HttpForwarder {
+    /// Returns a forwarder posting to every one of `urls`.
+    pub fn new(urls: impl IntoIterator<Item = impl Into<String>>) -> HttpForwarder {
+        HttpForwarder {
+            client: reqwest::Client::new(),
+            urls: urls.into_iter().map(Into::into).collect(),
+            authorization: None,
+            retries: 3,
+            backoff: Duration::from_millis(500),
+        }
+    }
+
+
+    /// Sends `authorization` as the `Authorization` header, for the services to check like top.gg's
+    /// webhooks are checked.
+    pub fn authorization(mut self, authorization: impl Into<String>) -> HttpForwarder {
+        self.authorization = Some(authorization.into());
+        self
+    }
+
+
+    /// Tries each failed post again up to `retries` times, waiting `backoff` before the first retry
+    /// and twice as long before each one after, up to a minute. It's 3 retries from half a second by
+    /// default.
+    pub fn retries(mut self, retries: u32, backoff: Duration) -> HttpForwarder {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+
+
+    /// Posts the event to `url`, retrying if it failed in a way that might not happen again: the
+    /// request not being sent, a 429 or a 5xx.
+    async fn post(&self, url: &str, event: &Event) -> Result<(), String> {
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            let mut req = self.client.post(url).json(event);
+            if let Some(authorization) = &self.authorization {
+                req = req.header("Authorization", authorization);
+            }
+            let error = match req.send().await {
+                Ok(res) if res.status().is_success() => return Ok(()),
+                Ok(res) if res.status().is_server_error() || res.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                    format!("{} responded with {}", url, res.status())
+                }
+                Ok(res) => return Err(format!("{} responded with {}", url, res.status())),
+                Err(e) => format!("posting to {} failed: {}", url, e),
+            };
+            if attempt >= self.retries {
+                return Err(error);
+            }
+            event!(tracing::Level::DEBUG, error = %error, attempt, "retrying forwarding an event");
+            crate::rt::sleep(backoff).await;
+            backoff = backoff.saturating_mul(2).min(MAX_BACKOFF);
+            attempt += 1;
+        }
+    }
+}
+#[async_trait]
+impl EventSink for HttpForwarder {
+    /// Posts the event to every URL at once. Fails if any of them still failed after retrying.
+    async fn send(&self, event: &Event) -> Result<(), SinkError> {
+        let posts = self.urls.iter().map(|url| self.post(url, event));
+        let errors = future::join_all(posts)
+            .await
+            .into_iter()
+            .filter_map(Result::err)
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(SinkError::new(errors.join(", ")))
+    }
+}
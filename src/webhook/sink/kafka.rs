@@ -0,0 +1,66 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::producer::{FutureProducer, FutureRecord};
+
+use super::{EventSink, SinkError};
+use crate::webhook::Event;
+
+
+
+/// Produces each event to a Kafka topic, as the same JSON top.gg sent. Events are keyed by the ID of
+/// the user who voted, so one user's votes stay in order on the same partition. Needs the `kafka`
+/// feature.
+/// ## Examples
+/// ```
+/// use topgg::webhook::{EventSink, KafkaSink};
+///
+/// let sink = KafkaSink::connect("kafka-1:9092,kafka-2:9092", "topgg-votes")?;
+/// sink.run(events).await;
+/// ```
+pub struct KafkaSink {
+    producer: FutureProducer,
+    topic: String,
+    timeout: Duration,
+}
+impl KafkaSink {
+    /// Makes a producer for the comma separated `brokers`, producing to `topic`.
+    pub fn connect(brokers: &str, topic: &str) -> Result<KafkaSink, SinkError> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .create()
+            .map_err(SinkError::new)?;
+        Ok(KafkaSink::new(producer, topic))
+    }
+
+
+    /// Returns a sink using an existing producer, for when it needs more configuration than the
+    /// brokers.
+    pub fn new(producer: FutureProducer, topic: &str) -> KafkaSink {
+        KafkaSink {
+            producer,
+            topic: topic.to_string(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+
+    /// Sets how long an event waits at most for room in the producer's queue, 5 seconds by default.
+    pub fn timeout(mut self, timeout: Duration) -> KafkaSink {
+        self.timeout = timeout;
+        self
+    }
+}
+#[async_trait]
+impl EventSink for KafkaSink {
+    async fn send(&self, event: &Event) -> Result<(), SinkError> {
+        let json = serde_json::to_string(event).map_err(SinkError::new)?;
+        let key = event.user().to_string();
+        let record = FutureRecord::to(&self.topic).key(&key).payload(&json);
+        self.producer
+            .send(record, self.timeout)
+            .await
+            .map(|_| ())
+            .map_err(|(e, _)| SinkError::new(e))
+    }
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+
+use super::{EventSink, SinkError};
+use crate::webhook::Event;
+
+
+
+/// Publishes each event to a NATS subject, as the same JSON top.gg sent. Needs the `nats` feature.
+/// ## Examples
+/// ```
+/// use topgg::webhook::{EventSink, NatsSink};
+///
+/// let sink = NatsSink::connect("nats://127.0.0.1:4222", "topgg.votes").await?;
+/// sink.run(events).await;
+/// ```
+#[derive(Clone)]
+pub struct NatsSink {
+    client: async_nats::Client,
+    subject: String,
+    flush: bool,
+}
+impl NatsSink {
+    /// Connects to the NATS server at `url`, publishing to `subject`.
+    pub async fn connect(url: &str, subject: &str) -> Result<NatsSink, SinkError> {
+        let client = async_nats::connect(url).await.map_err(SinkError::new)?;
+        Ok(NatsSink::new(client, subject))
+    }
+
+
+    /// Returns a sink using an existing client.
+    pub fn new(client: async_nats::Client, subject: &str) -> NatsSink {
+        NatsSink {
+            client,
+            subject: subject.to_string(),
+            flush: false,
+        }
+    }
+
+
+    /// Waits for each event to be written to the server before `send` returns, instead of only until
+    /// it's queued to be sent. It's slower, but an event lost to the connection dropping is reported.
+    pub fn flush(mut self, flush: bool) -> NatsSink {
+        self.flush = flush;
+        self
+    }
+}
+#[async_trait]
+impl EventSink for NatsSink {
+    async fn send(&self, event: &Event) -> Result<(), SinkError> {
+        let json = serde_json::to_string(event).map_err(SinkError::new)?;
+        self.client
+            .publish(self.subject.clone(), json.into())
+            .await
+            .map_err(SinkError::new)?;
+        if self.flush {
+            self.client.flush().await.map_err(SinkError::new)?;
+        }
+        Ok(())
+    }
+}
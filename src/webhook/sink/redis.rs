@@ -0,0 +1,101 @@
+use ::redis::aio::ConnectionManager;
+use async_trait::async_trait;
+
+use super::{EventSink, SinkError};
+use crate::webhook::Event;
+
+
+
+/// Publishes each event to Redis, so reward workers and analytics in other processes can consume votes
+/// without running the webhook server. Events are sent as the same JSON top.gg sent, to a pub/sub
+/// channel by default or appended to a stream. Needs the `redis` feature.
+/// ## Examples
+/// ```
+/// use topgg::webhook::{EventSink, RedisPublisher};
+///
+/// let publisher = RedisPublisher::connect("redis://127.0.0.1/").await?.stream("topgg:votes");
+/// publisher.run(events).await;
+/// ```
+#[derive(Clone)]
+pub struct RedisPublisher {
+    conn: ConnectionManager,
+    target: Target,
+}
+
+#[derive(Clone)]
+enum Target {
+    Channel(String),
+    Stream { key: String, max_len: Option<usize> },
+}
+
+impl RedisPublisher {
+    /// Connects to the Redis server at `url`, reconnecting by itself if the connection drops.
+    pub async fn connect(url: &str) -> Result<RedisPublisher, SinkError> {
+        let client = ::redis::Client::open(url).map_err(SinkError::new)?;
+        let conn = ConnectionManager::new(client).await.map_err(SinkError::new)?;
+        Ok(RedisPublisher::new(conn))
+    }
+
+
+    /// Returns a publisher using an existing connection, publishing to the `topgg:votes` channel.
+    pub fn new(conn: ConnectionManager) -> RedisPublisher {
+        RedisPublisher {
+            conn,
+            target: Target::Channel("topgg:votes".to_string()),
+        }
+    }
+
+
+    /// Publishes to the pub/sub channel `channel`. Only subscribers connected at the time receive an
+    /// event.
+    pub fn channel(mut self, channel: &str) -> RedisPublisher {
+        self.target = Target::Channel(channel.to_string());
+        self
+    }
+
+
+    /// Appends to the stream at `key` instead of publishing to a channel, so events are kept for
+    /// consumers which aren't running yet. Each entry has the event's JSON in its `event` field.
+    pub fn stream(mut self, key: &str) -> RedisPublisher {
+        self.target = Target::Stream {
+            key: key.to_string(),
+            max_len: None,
+        };
+        self
+    }
+
+
+    /// Trims the stream to about `max_len` entries as events are added, instead of keeping every one.
+    /// Does nothing when publishing to a channel.
+    pub fn max_len(mut self, max_len: usize) -> RedisPublisher {
+        if let Target::Stream { max_len: limit, .. } = &mut self.target {
+            *limit = Some(max_len);
+        }
+        self
+    }
+}
+#[async_trait]
+impl EventSink for RedisPublisher {
+    async fn send(&self, event: &Event) -> Result<(), SinkError> {
+        let json = serde_json::to_string(event).map_err(SinkError::new)?;
+        let mut cmd = match &self.target {
+            Target::Channel(channel) => {
+                let mut cmd = ::redis::cmd("PUBLISH");
+                cmd.arg(channel).arg(json);
+                cmd
+            }
+            Target::Stream { key, max_len } => {
+                let mut cmd = ::redis::cmd("XADD");
+                cmd.arg(key);
+                if let Some(max_len) = max_len {
+                    cmd.arg("MAXLEN").arg("~").arg(*max_len);
+                }
+                cmd.arg("*").arg("event").arg(json);
+                cmd
+            }
+        };
+        cmd.query_async::<_, ()>(&mut self.conn.clone())
+            .await
+            .map_err(SinkError::new)
+    }
+}